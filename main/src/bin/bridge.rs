@@ -0,0 +1,133 @@
+// Multi-platform ingestion bridge
+// Long-polls Telegram (if configured) and exposes a generic webhook so any
+// other chat platform can feed the same MessageEvent pipeline Discord uses.
+// Run with: cargo run --bin bridge
+
+use dotenv::dotenv;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use warp::Filter;
+use Quiry::{
+    config::Config,
+    kafka_producer::KafkaProducer,
+    kafka_types::KafkaMessage,
+    message_source::MessageSource,
+    schema::{MessageEvent, Platform},
+    telegram::TelegramSource,
+    telemetry,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+
+    let cfg = Config::from_env();
+    telemetry::init_tracing(&cfg);
+
+    let producer = Arc::new(KafkaProducer::new(&cfg)?);
+
+    let port = std::env::var("PORT").unwrap_or_else(|_| "8086".to_string()).parse::<u16>().unwrap_or(8086);
+    info!("Starting multi-platform ingestion bridge on port {}...", port);
+
+    let telegram_task = {
+        let cfg = cfg.clone();
+        let producer = producer.clone();
+        tokio::spawn(async move { run_telegram_source(cfg, producer).await })
+    };
+
+    let bridge_route = warp::path!("bridge" / "message")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_producer(producer.clone()))
+        .and_then(handle_bridge_message);
+
+    let root_route = warp::path::end()
+        .and(warp::get())
+        .map(|| "Quiry Ingestion Bridge - POST /bridge/message");
+
+    let routes = bridge_route.or(root_route);
+    let server = tokio::spawn(async move {
+        warp::serve(routes).run(([0, 0, 0, 0], port)).await;
+    });
+
+    tokio::select! {
+        _ = server => info!("Bridge HTTP server stopped"),
+        _ = telegram_task => info!("Telegram source stopped"),
+    }
+
+    Ok(())
+}
+
+/// Long-polls `TelegramSource::poll` in a loop, forwarding every
+/// `MessageEvent` it returns to Kafka exactly like Discord's gateway
+/// `message` handler forwards `serenity` events. A disabled (no
+/// `TELEGRAM_BOT_TOKEN`) or errored source just idles rather than crashing
+/// the bridge, matching how `Handler::new` degrades when Kafka itself is
+/// unavailable.
+async fn run_telegram_source(cfg: Config, producer: Arc<KafkaProducer>) {
+    let Some(source) = TelegramSource::new(&cfg) else {
+        warn!("TELEGRAM_BOT_TOKEN not set, Telegram source disabled");
+        return std::future::pending::<()>().await;
+    };
+
+    loop {
+        match source.poll().await {
+            Ok(events) => {
+                for event in events {
+                    forward_to_kafka(&producer, event).await;
+                }
+            }
+            Err(err) => {
+                error!(error = %err, "Telegram poll failed, retrying after backoff");
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeMessagePayload {
+    channel_id: String,
+    author_id: String,
+    text: String,
+    guild_id: Option<String>,
+    timestamp: Option<String>,
+}
+
+/// Generic webhook for any platform that doesn't warrant a dedicated
+/// `MessageSource` adapter - a relay just POSTs the fields it has and the
+/// rest of the pipeline treats it identically to a Discord or Telegram
+/// message.
+async fn handle_bridge_message(
+    payload: BridgeMessagePayload,
+    producer: Arc<KafkaProducer>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let event = MessageEvent {
+        id: format!("bridge:{}", Uuid::new_v4()),
+        platform: Platform::Bridge,
+        guild_id: payload.guild_id,
+        channel_id: payload.channel_id,
+        author_id: payload.author_id,
+        timestamp: payload.timestamp.unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+        text: payload.text,
+    };
+
+    forward_to_kafka(&producer, event).await;
+    Ok(warp::reply::with_status("accepted", warp::http::StatusCode::ACCEPTED))
+}
+
+async fn forward_to_kafka(producer: &KafkaProducer, event: MessageEvent) {
+    let message_id = event.id.clone();
+    let kafka_message = KafkaMessage::new_discord_message(event);
+    if let Err(err) = producer.send_discord_message(kafka_message).await {
+        error!(message_id, error = %err, "Failed to forward bridged message to Kafka");
+    }
+}
+
+fn with_producer(
+    producer: Arc<KafkaProducer>,
+) -> impl Filter<Extract = (Arc<KafkaProducer>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || producer.clone())
+}
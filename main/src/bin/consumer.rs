@@ -2,31 +2,31 @@
 // Run with: cargo run --bin consumer
 
 use dotenv::dotenv;
-use tracing_subscriber;
 use tracing::info;
 use std::sync::Arc;
 use warp::Filter;
 use Quiry::{
-    config::Config, 
-    kafka_consumer::KafkaConsumer, 
+    config::Config,
+    kafka_consumer::KafkaConsumer,
     kafka_types::DISCORD_MESSAGES_TOPIC,
     metrics::MetricsRegistry,
     health::HealthChecker,
+    telemetry,
 };
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     dotenv().ok();
-    tracing_subscriber::fmt::init();
 
     let cfg = Config::from_env();
+    telemetry::init_tracing(&cfg);
     let metrics_registry = Arc::new(MetricsRegistry::new());
     let health_checker = Arc::new(HealthChecker::new());
     
     let port = std::env::var("PORT").unwrap_or_else(|_| "8084".to_string()).parse::<u16>().unwrap_or(8084);
     info!("Starting Kafka Consumer Service on port {}...", port);
     
-    let mut consumer = KafkaConsumer::new(cfg.clone())?;
+    let mut consumer = KafkaConsumer::new(cfg.clone(), health_checker.consumer_readiness())?;
     
     // Subscribe to Discord messages topic
     consumer.subscribe_to_topics(&[DISCORD_MESSAGES_TOPIC]).await?;
@@ -45,11 +45,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .and(with_config(cfg.clone()))
         .and_then(handle_health);
 
+    let ready_route = warp::path("ready")
+        .and(warp::get())
+        .and(with_health_checker(health_checker.clone()))
+        .and(with_config(cfg.clone()))
+        .and_then(handle_ready);
+
     let root_route = warp::path::end()
         .and(warp::get())
-        .map(|| "Quiry Consumer Service - /metrics, /health");
+        .map(|| "Quiry Consumer Service - /metrics, /health, /ready");
 
-    let routes = metrics_route.or(health_route).or(root_route);
+    let routes = metrics_route.or(health_route).or(ready_route).or(root_route);
     
     // Start HTTP server in background
     let server = tokio::spawn(async move {
@@ -102,7 +108,7 @@ async fn handle_health(
     config: Config,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let health_status = health_checker
-        .get_overall_health(&config.elasticsearch_url, &config.pinecone_host)
+        .get_overall_health(&config)
         .await;
     
     let json_response = serde_json::to_string_pretty(&health_status)
@@ -114,3 +120,19 @@ async fn handle_health(
         "application/json",
     ))
 }
+
+async fn handle_ready(
+    health_checker: Arc<HealthChecker>,
+    config: Config,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let max_poll_age = std::time::Duration::from_millis(config.kafka_ready_max_poll_age_ms);
+    let readiness = health_checker.get_readiness(max_poll_age);
+
+    let json_response = serde_json::to_string_pretty(&readiness)
+        .unwrap_or_else(|_| "{\"error\": \"Failed to serialize readiness status\"}".to_string());
+
+    Ok(warp::reply::with_status(
+        warp::reply::with_header(json_response, "Content-Type", "application/json"),
+        if readiness.ready { warp::http::StatusCode::OK } else { warp::http::StatusCode::SERVICE_UNAVAILABLE },
+    ))
+}
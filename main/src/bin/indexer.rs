@@ -139,7 +139,7 @@ async fn handle_health(
     config: Config,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let health_status = health_checker
-        .get_overall_health(&config.elasticsearch_url, &config.pinecone_host)
+        .get_overall_health(&config)
         .await;
     
     let json_response = serde_json::to_string_pretty(&health_status)
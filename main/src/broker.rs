@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use opentelemetry::Context as OtelContext;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Selects which [`MessageConsumer`]/[`MessageProducer`] pair the application
+/// should construct, mirroring [`crate::vector_store::VectorStoreBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrokerBackend {
+    Kafka,
+    InMemory,
+}
+
+impl BrokerBackend {
+    pub fn from_env_str(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "memory" | "in-memory" | "in_memory" => BrokerBackend::InMemory,
+            _ => BrokerBackend::Kafka,
+        }
+    }
+}
+
+/// One message handed back by [`MessageConsumer::recv`], backend-agnostic so
+/// `KafkaConsumer` doesn't need to know whether it came from `rdkafka` or an
+/// [`InMemoryBroker`].
+pub struct ConsumedMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub payload: Vec<u8>,
+    /// Remote trace context extracted from the message's transport headers,
+    /// if the backend carries any. The in-memory backend has no headers, so
+    /// this is always an empty context there.
+    pub trace_context: OtelContext,
+}
+
+/// Abstracts the consume/commit side of a message broker so `KafkaConsumer`
+/// depends on this trait rather than concretely on `rdkafka::StreamConsumer`,
+/// the same way [`crate::vector_store::VectorStore`] abstracts Pinecone vs.
+/// the embedded HNSW backend.
+#[async_trait]
+pub trait MessageConsumer: Send + Sync {
+    async fn subscribe(&self, topics: &[&str]) -> Result<(), DynErr>;
+    async fn recv(&self) -> Result<ConsumedMessage, DynErr>;
+    /// Commits, per `(topic, partition)`, the offset of the last message
+    /// processed. Implementations should commit `offset + 1` if their
+    /// backend uses Kafka's "next offset to read" convention.
+    async fn commit(&self, commits: &[((String, i32), i64)]) -> Result<(), DynErr>;
+}
+
+/// Abstracts the produce side of a message broker.
+#[async_trait]
+pub trait MessageProducer: Send + Sync {
+    async fn send(&self, topic: &str, key: &str, payload: Vec<u8>) -> Result<(), DynErr>;
+}
+
+/// In-process broker backed by a per-topic `VecDeque`, so the
+/// `process_message` -> chunking -> (mocked) embedding/Pinecone pipeline can
+/// be unit-tested end-to-end without a running Kafka cluster, mirroring
+/// arroyo's local broker. A single instance implements both
+/// [`MessageProducer`] and [`MessageConsumer`] - clone it to hand one side to
+/// a producer and keep the other for a consumer.
+#[derive(Clone, Default)]
+pub struct InMemoryBroker {
+    topics: Arc<Mutex<HashMap<String, VecDeque<(i64, Vec<u8>)>>>>,
+    next_offset: Arc<Mutex<HashMap<String, i64>>>,
+    subscribed: Arc<Mutex<Vec<String>>>,
+}
+
+impl InMemoryBroker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, topic: &str, payload: Vec<u8>) {
+        let mut next_offset = self.next_offset.lock().unwrap();
+        let offset = next_offset.entry(topic.to_string()).or_insert(0);
+        let this_offset = *offset;
+        *offset += 1;
+
+        self.topics
+            .lock()
+            .unwrap()
+            .entry(topic.to_string())
+            .or_default()
+            .push_back((this_offset, payload));
+    }
+
+    /// Enqueues a `KafkaMessage` directly, for tests that want to seed the
+    /// broker without going through [`MessageProducer::send`].
+    pub fn enqueue(&self, topic: &str, message: &crate::kafka_types::KafkaMessage) -> Result<(), DynErr> {
+        let payload = serde_json::to_vec(message)?;
+        self.push(topic, payload);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageProducer for InMemoryBroker {
+    async fn send(&self, topic: &str, _key: &str, payload: Vec<u8>) -> Result<(), DynErr> {
+        self.push(topic, payload);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageConsumer for InMemoryBroker {
+    async fn subscribe(&self, topics: &[&str]) -> Result<(), DynErr> {
+        *self.subscribed.lock().unwrap() = topics.iter().map(|t| t.to_string()).collect();
+        Ok(())
+    }
+
+    async fn recv(&self) -> Result<ConsumedMessage, DynErr> {
+        loop {
+            let subscribed = self.subscribed.lock().unwrap().clone();
+            let mut topics = self.topics.lock().unwrap();
+            for topic in &subscribed {
+                if let Some(queue) = topics.get_mut(topic) {
+                    if let Some((offset, payload)) = queue.pop_front() {
+                        return Ok(ConsumedMessage {
+                            topic: topic.clone(),
+                            partition: 0,
+                            offset,
+                            payload,
+                            trace_context: OtelContext::new(),
+                        });
+                    }
+                }
+            }
+            drop(topics);
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn commit(&self, _commits: &[((String, i32), i64)]) -> Result<(), DynErr> {
+        // Messages are already removed from the queue as `recv` hands them
+        // out, so there's nothing further to persist.
+        Ok(())
+    }
+}
@@ -2,9 +2,9 @@ use std::collections::HashMap;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{info, warn, error};
 use uuid::Uuid;
-use crate::schema::{MessageEvent, MessageChunk};
+use crate::schema::{MessageEvent, MessageChunk, Platform};
 use crate::config::Config;
-use crate::cohere::{get_embedding, generate_summary};
+use crate::cohere::{get_embedding, generate_summary, EmbeddingInputType};
 use crate::pinecone::upsert_chunk_to_pinecone;
 
 const MAX_CHUNK_SIZE: usize = 12;
@@ -86,6 +86,7 @@ impl MessageBuffer {
 
         let chunk = MessageChunk {
             chunk_id,
+            platform: first_msg.platform,
             guild_id: first_msg.guild_id.clone(),
             channel_id: first_msg.channel_id.clone(),
             first_msg_id: first_msg.id.clone(),
@@ -117,15 +118,18 @@ impl ChunkManager {
         }
     }
 
-    fn get_buffer_key(guild_id: &Option<String>, channel_id: &str) -> String {
+    /// Namespaces the buffer key by platform too, so a Discord channel id
+    /// and a Telegram chat id can never collide into the same buffer.
+    fn get_buffer_key(platform: Platform, guild_id: &Option<String>, channel_id: &str) -> String {
         match guild_id {
-            Some(gid) => format!("{}:{}", gid, channel_id),
-            None => format!("dm:{}", channel_id),
+            Some(gid) => format!("{platform:?}:{gid}:{channel_id}"),
+            None => format!("{platform:?}:dm:{channel_id}"),
         }
     }
 
+    #[tracing::instrument(skip(self, cfg, message), fields(message_id = %message.id))]
     pub async fn process_message(&mut self, cfg: &Config, message: MessageEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let buffer_key = Self::get_buffer_key(&message.guild_id, &message.channel_id);
+        let buffer_key = Self::get_buffer_key(message.platform, &message.guild_id, &message.channel_id);
 
         // Parse message timestamp
         let message_time = chrono::DateTime::parse_from_rfc3339(&message.timestamp)
@@ -189,7 +193,7 @@ impl ChunkManager {
             &chunk.full_text
         };
 
-        match get_embedding(cfg, text_to_embed).await {
+        match get_embedding(cfg, text_to_embed, EmbeddingInputType::SearchDocument).await {
             Ok(embedding) => {
                 if let Err(err) = upsert_chunk_to_pinecone(cfg, &chunk, embedding).await {
                     error!(chunk_id=?chunk.chunk_id, error=?err, "Failed to upsert chunk to Pinecone");
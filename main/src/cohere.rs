@@ -1,24 +1,55 @@
-use reqwest::Client;
+use futures::stream::{self, Stream};
 use serde_json::json;
 use tracing::{info, warn};
-use crate::{config::Config, schema::{QueryResult, ChunkQueryResult}, metrics::EMBEDDING_GENERATION_DURATION};
+use crate::{
+    config::Config,
+    schema::{QueryResult, ChunkQueryResult},
+    metrics::{EMBEDDING_GENERATION_DURATION, COHERE_CHAT_DURATION},
+    http_client::{shared_client, with_retry, RetryConfig, COHERE_BREAKER},
+};
 
 type DynErr = Box<dyn std::error::Error + Send + Sync>;
 
-pub async fn get_embedding(cfg: &Config, text: &str) -> Result<Vec<f32>, DynErr> {
-    let _timer = EMBEDDING_GENERATION_DURATION.start_timer();
-    let client = Client::new();
+/// Cohere's v3 embedding models are asymmetric: the vector for a stored
+/// document and the vector for a query against it must be produced with
+/// different `input_type`s, or cosine similarity between them degrades.
+/// Mirrors the `input_type` values Cohere's `/v1/embed` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingInputType {
+    /// Text being stored for later retrieval (ingested messages/chunks).
+    SearchDocument,
+    /// A question being embedded to search against stored documents.
+    SearchQuery,
+    /// Text being embedded for a downstream classifier, not retrieval.
+    Classification,
+    /// Text being embedded for clustering, not retrieval.
+    Clustering,
+}
 
-    let res = client
-        .post("https://api.cohere.ai/v1/embed")
-        .bearer_auth(&cfg.cohere_key)
-        .json(&json!({
-            "model": "embed-english-v3.0",
-            "input_type": "search_document",
-            "texts": [text]
-        }))
-        .send()
-        .await?;
+impl EmbeddingInputType {
+    fn as_cohere_str(&self) -> &'static str {
+        match self {
+            EmbeddingInputType::SearchDocument => "search_document",
+            EmbeddingInputType::SearchQuery => "search_query",
+            EmbeddingInputType::Classification => "classification",
+            EmbeddingInputType::Clustering => "clustering",
+        }
+    }
+}
+
+#[tracing::instrument(skip(cfg, text), fields(text_len = text.len(), input_type = ?input_type))]
+pub async fn get_embedding(cfg: &Config, text: &str, input_type: EmbeddingInputType) -> Result<Vec<f32>, DynErr> {
+    let res = with_retry(&COHERE_BREAKER, &EMBEDDING_GENERATION_DURATION, &RetryConfig::default(), || {
+        shared_client()
+            .post("https://api.cohere.ai/v1/embed")
+            .bearer_auth(&cfg.cohere_key)
+            .json(&json!({
+                "model": "embed-english-v3.0",
+                "input_type": input_type.as_cohere_str(),
+                "texts": [text]
+            }))
+            .send()
+    }).await?;
 
     if !res.status().is_success() {
         return Err(format!("Cohere error: {}", res.text().await?).into());
@@ -36,32 +67,31 @@ pub async fn get_embedding(cfg: &Config, text: &str) -> Result<Vec<f32>, DynErr>
 }
 
 pub async fn generate_response(cfg: &Config, query: &str, context_messages: &[QueryResult]) -> Result<String, DynErr> {
-    let client = Client::new();
-
     let context = context_messages
         .iter()
         .map(|msg| format!("- {}", msg.text))
         .collect::<Vec<_>>()
         .join("\n");
 
-    let res = client
-        .post("https://api.cohere.ai/v1/chat")
-        .bearer_auth(&cfg.cohere_key)
-        .json(&json!({
-            "model": "command-r-08-2024",
-            "message": query,
-            "preamble": format!(
-                "You are a helpful assistant that answers questions based on Discord message history. \
-                Here are some relevant messages from the conversation:\n\n{}\n\n\
-                Please provide a helpful answer based on the context above. If the context doesn't contain \
-                enough information to answer the question, say so.",
-                context
-            ),
-            "max_tokens": 300,
-            "temperature": 0.7
-        }))
-        .send()
-        .await?;
+    let res = with_retry(&COHERE_BREAKER, &COHERE_CHAT_DURATION, &RetryConfig::default(), || {
+        shared_client()
+            .post("https://api.cohere.ai/v1/chat")
+            .bearer_auth(&cfg.cohere_key)
+            .json(&json!({
+                "model": "command-r-08-2024",
+                "message": query,
+                "preamble": format!(
+                    "You are a helpful assistant that answers questions based on Discord message history. \
+                    Here are some relevant messages from the conversation:\n\n{}\n\n\
+                    Please provide a helpful answer based on the context above. If the context doesn't contain \
+                    enough information to answer the question, say so.",
+                    context
+                ),
+                "max_tokens": 300,
+                "temperature": 0.7
+            }))
+            .send()
+    }).await?;
 
     if !res.status().is_success() {
         return Err(format!("Cohere generate error: {}", res.text().await?).into());
@@ -79,25 +109,24 @@ pub async fn generate_response(cfg: &Config, query: &str, context_messages: &[Qu
 }
 
 pub async fn generate_summary(cfg: &Config, text: &str) -> Result<String, DynErr> {
-    let client = Client::new();
-
-    let res = client
-        .post("https://api.cohere.ai/v1/chat")
-        .bearer_auth(&cfg.cohere_key)
-        .json(&json!({
-            "model": "command-r-08-2024",
-            "message": "Please provide a concise summary of this Discord conversation in 2-3 sentences.",
-            "preamble": format!(
-                "You are a helpful assistant that summarizes Discord conversations. \
-                Here is the conversation to summarize:\n\n{}\n\n\
-                Focus on the main topics discussed and key information shared.",
-                text
-            ),
-            "max_tokens": 150,
-            "temperature": 0.3
-        }))
-        .send()
-        .await?;
+    let res = with_retry(&COHERE_BREAKER, &COHERE_CHAT_DURATION, &RetryConfig::default(), || {
+        shared_client()
+            .post("https://api.cohere.ai/v1/chat")
+            .bearer_auth(&cfg.cohere_key)
+            .json(&json!({
+                "model": "command-r-08-2024",
+                "message": "Please provide a concise summary of this Discord conversation in 2-3 sentences.",
+                "preamble": format!(
+                    "You are a helpful assistant that summarizes Discord conversations. \
+                    Here is the conversation to summarize:\n\n{}\n\n\
+                    Focus on the main topics discussed and key information shared.",
+                    text
+                ),
+                "max_tokens": 150,
+                "temperature": 0.3
+            }))
+            .send()
+    }).await?;
 
     if !res.status().is_success() {
         return Err(format!("Cohere summarize error: {}", res.text().await?).into());
@@ -114,9 +143,10 @@ pub async fn generate_summary(cfg: &Config, text: &str) -> Result<String, DynErr
     }
 }
 
-pub async fn generate_response_from_chunks(cfg: &Config, query: &str, context_chunks: &[ChunkQueryResult]) -> Result<String, DynErr> {
-    let client = Client::new();
-
+/// Builds the `/v1/chat` preamble shared by the blocking and streamed
+/// chunk-grounded generators, so the two stay in lockstep instead of drifting
+/// apart the next time the prompt is tuned.
+fn chunks_preamble(context_chunks: &[ChunkQueryResult]) -> String {
     let context = context_chunks
         .iter()
         .map(|chunk| {
@@ -133,52 +163,57 @@ pub async fn generate_response_from_chunks(cfg: &Config, query: &str, context_ch
         .collect::<Vec<_>>()
         .join("\n\n");
 
-    let res = client
-        .post("https://api.cohere.ai/v1/chat")
-        .bearer_auth(&cfg.cohere_key)
-        .json(&json!({
-            "model": "command-r-08-2024",
-            "message": query,
-            "preamble": format!(
-                "You are a helpful assistant that answers questions using ONLY the Discord conversation excerpts provided below.
-
-                CONTEXT
-                {}
-
-                GUIDELINES
-                1) Attribution & names
-                - Pay close attention to who said what using the Speaker field.
-                - Use the person's display name/nickname as shown in the context.
-                - Never reveal or repeat raw user IDs in your answer. If only an ID is present (no name), refer to them generically as \"a participant\".
-
-                2) Evidence-first accuracy
-                - Base every statement strictly on the CONTEXT. Do not invent facts or rely on outside knowledge.
-                - When the user asks \"who said X?\", identify the speaker by display name exactly as it appears in the context.
-                - If multiple people said similar things, list each relevant speaker with a short quote snippet for disambiguation.
-
-                3) Quotes & formatting
-                - When helpful, include short quotes from the context using Markdown blockquotes:
-                    > \"quoted message\"
-                    â€” display name, optional timestamp if available
-                - Do NOT include user IDs. Do NOT @mention users or roles (avoid pinging). Use plain names (or backticks) instead.
-
-                4) Not enough info
-                - If the context is insufficient to answer, say so clearly and specify exactly what's missing (e.g., \"I can't find any message where a participant confirms shipping on Friday.\").
-
-                5) Style
-                - Be concise and direct. Answer first, then show minimal supporting quotes if needed.
-                - Preserve important timing (dates/times) and channel/thread distinctions when present.
-
-                OUTPUT
-                - Provide the best possible answer grounded in the CONTEXT.
-                - Do not disclose user IDs. Do not include this instruction block in your reply.",
-                context
-            ),
-            "max_tokens": 300,
-            "temperature": 0.7
-        }))
-        .send()
-        .await?;
+    format!(
+        "You are a helpful assistant that answers questions using ONLY the Discord conversation excerpts provided below.
+
+        CONTEXT
+        {}
+
+        GUIDELINES
+        1) Attribution & names
+        - Pay close attention to who said what using the Speaker field.
+        - Use the person's display name/nickname as shown in the context.
+        - Never reveal or repeat raw user IDs in your answer. If only an ID is present (no name), refer to them generically as \"a participant\".
+
+        2) Evidence-first accuracy
+        - Base every statement strictly on the CONTEXT. Do not invent facts or rely on outside knowledge.
+        - When the user asks \"who said X?\", identify the speaker by display name exactly as it appears in the context.
+        - If multiple people said similar things, list each relevant speaker with a short quote snippet for disambiguation.
+
+        3) Quotes & formatting
+        - When helpful, include short quotes from the context using Markdown blockquotes:
+            > \"quoted message\"
+            — display name, optional timestamp if available
+        - Do NOT include user IDs. Do NOT @mention users or roles (avoid pinging). Use plain names (or backticks) instead.
+
+        4) Not enough info
+        - If the context is insufficient to answer, say so clearly and specify exactly what's missing (e.g., \"I can't find any message where a participant confirms shipping on Friday.\").
+
+        5) Style
+        - Be concise and direct. Answer first, then show minimal supporting quotes if needed.
+        - Preserve important timing (dates/times) and channel/thread distinctions when present.
+
+        OUTPUT
+        - Provide the best possible answer grounded in the CONTEXT.
+        - Do not disclose user IDs. Do not include this instruction block in your reply.",
+        context
+    )
+}
+
+pub async fn generate_response_from_chunks(cfg: &Config, query: &str, context_chunks: &[ChunkQueryResult]) -> Result<String, DynErr> {
+    let res = with_retry(&COHERE_BREAKER, &COHERE_CHAT_DURATION, &RetryConfig::default(), || {
+        shared_client()
+            .post("https://api.cohere.ai/v1/chat")
+            .bearer_auth(&cfg.cohere_key)
+            .json(&json!({
+                "model": "command-r-08-2024",
+                "message": query,
+                "preamble": chunks_preamble(context_chunks),
+                "max_tokens": 300,
+                "temperature": 0.7
+            }))
+            .send()
+    }).await?;
 
     if !res.status().is_success() {
         return Err(format!("Cohere generate error: {}", res.text().await?).into());
@@ -194,3 +229,87 @@ pub async fn generate_response_from_chunks(cfg: &Config, query: &str, context_ch
         Err("No generated text found".into())
     }
 }
+
+/// Streaming twin of [`generate_response_from_chunks`] for `/ask`, which
+/// wants to type its answer out live instead of sitting on "Searching..."
+/// for the several seconds a full completion takes. Sets `"stream": true`
+/// and reads Cohere's newline-delimited event stream, yielding each
+/// `text-generation` delta as it arrives and stopping at `stream-end`.
+/// Internal callers that just want the final string (summarization, the
+/// Kafka consumer's `/ask` handling) should keep using the blocking
+/// [`generate_response_from_chunks`] above.
+pub async fn generate_response_from_chunks_streamed(
+    cfg: &Config,
+    query: &str,
+    context_chunks: &[ChunkQueryResult],
+) -> Result<impl Stream<Item = Result<String, DynErr>>, DynErr> {
+    let res = shared_client()
+        .post("https://api.cohere.ai/v1/chat")
+        .bearer_auth(&cfg.cohere_key)
+        .json(&json!({
+            "model": "command-r-08-2024",
+            "message": query,
+            "preamble": chunks_preamble(context_chunks),
+            "max_tokens": 300,
+            "temperature": 0.7,
+            "stream": true
+        }))
+        .send()
+        .await?;
+
+    if !res.status().is_success() {
+        return Err(format!("Cohere generate error: {}", res.text().await?).into());
+    }
+
+    Ok(cohere_event_stream(res))
+}
+
+/// Turns a streaming `/v1/chat` response into a stream of answer deltas.
+/// Cohere's stream is newline-delimited JSON events rather than a single
+/// body, so this buffers raw bytes until it can split off a full line,
+/// forwards `text-generation` events' `text` field, and ends the stream at
+/// `stream-end`. A connection that closes before `stream-end` surfaces as an
+/// `Err` rather than silently truncating the answer.
+fn cohere_event_stream(res: reqwest::Response) -> impl Stream<Item = Result<String, DynErr>> {
+    stream::unfold((res, Vec::new(), false), |(mut res, mut buf, mut ended)| async move {
+        loop {
+            if ended {
+                return None;
+            }
+
+            if let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buf.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+
+                let event: serde_json::Value = match serde_json::from_str(line) {
+                    Ok(event) => event,
+                    Err(err) => return Some((Err(format!("Cohere stream parse error: {err}").into()), (res, buf, true))),
+                };
+
+                match event["event_type"].as_str() {
+                    Some("text-generation") => {
+                        let text = event["text"].as_str().unwrap_or_default().to_string();
+                        return Some((Ok(text), (res, buf, ended)));
+                    }
+                    Some("stream-end") => {
+                        ended = true;
+                        continue;
+                    }
+                    _ => continue,
+                }
+            }
+
+            match res.chunk().await {
+                Ok(Some(chunk)) => buf.extend_from_slice(&chunk),
+                Ok(None) => {
+                    return Some((Err("Cohere stream closed before stream-end".into()), (res, buf, true)));
+                }
+                Err(err) => return Some((Err(err.into()), (res, buf, true))),
+            }
+        }
+    })
+}
@@ -1,5 +1,7 @@
+use rdkafka::ClientConfig;
 use std::env;
 
+#[derive(Clone)]
 pub struct Config {
     pub discord_token: String,
     pub cohere_key: String,
@@ -9,6 +11,34 @@ pub struct Config {
     pub namespace: String,
     pub kafka_brokers: String,
     pub kafka_group_id: String,
+    pub kafka_dlq_suffix: String,
+    pub kafka_max_retries: u32,
+    pub kafka_retry_base_delay_ms: u64,
+    pub kafka_schema_dir: String,
+    pub kafka_commit_batch_size: usize,
+    pub kafka_commit_interval_ms: u64,
+    pub kafka_query_responses_topic: String,
+    pub kafka_ready_max_poll_age_ms: u64,
+    pub kafka_broker_backend: String,
+    pub kafka_partition_count: u32,
+    pub kafka_security_protocol: String,
+    pub kafka_sasl_mechanism: Option<String>,
+    pub kafka_sasl_username: Option<String>,
+    pub kafka_sasl_password: Option<String>,
+    pub kafka_ssl_ca_location: Option<String>,
+    pub kafka_ssl_cert_location: Option<String>,
+    pub kafka_ssl_key_location: Option<String>,
+    pub ingest_num_workers: usize,
+    pub elasticsearch_url: String,
+    pub elasticsearch_index: String,
+    pub elasticsearch_bulk_max_bytes: usize,
+    pub elasticsearch_analyzer_profile: String,
+    pub vector_store_backend: String,
+    pub hnsw_persist_path: String,
+    pub otlp_endpoint: Option<String>,
+    pub otel_service_name: String,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_poll_timeout_secs: u64,
 }
 
 impl Config {
@@ -30,6 +60,101 @@ impl Config {
                 .unwrap_or_else(|_| "localhost:9092".into()),
             kafka_group_id: env::var("KAFKA_GROUP_ID")
                 .unwrap_or_else(|_| "quiry-bot".into()),
+            kafka_dlq_suffix: env::var("KAFKA_DLQ_SUFFIX")
+                .unwrap_or_else(|_| ".dlq".into()),
+            kafka_max_retries: env::var("KAFKA_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            kafka_retry_base_delay_ms: env::var("KAFKA_RETRY_BASE_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(200),
+            kafka_schema_dir: env::var("KAFKA_SCHEMA_DIR")
+                .unwrap_or_else(|_| "schemas".into()),
+            kafka_commit_batch_size: env::var("KAFKA_COMMIT_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+            kafka_commit_interval_ms: env::var("KAFKA_COMMIT_INTERVAL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5000),
+            kafka_query_responses_topic: env::var("QUERY_RESPONSES_TOPIC")
+                .unwrap_or_else(|_| "query-responses".into()),
+            kafka_ready_max_poll_age_ms: env::var("KAFKA_READY_MAX_POLL_AGE_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60_000),
+            kafka_broker_backend: env::var("KAFKA_BROKER_BACKEND")
+                .unwrap_or_else(|_| "kafka".into()),
+            kafka_partition_count: env::var("KAFKA_PARTITION_COUNT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(12),
+            kafka_security_protocol: env::var("KAFKA_SECURITY_PROTOCOL")
+                .unwrap_or_else(|_| "plaintext".into()),
+            kafka_sasl_mechanism: env::var("KAFKA_SASL_MECHANISM").ok(),
+            kafka_sasl_username: env::var("KAFKA_SASL_USERNAME").ok(),
+            kafka_sasl_password: env::var("KAFKA_SASL_PASSWORD").ok(),
+            kafka_ssl_ca_location: env::var("KAFKA_SSL_CA_LOCATION").ok(),
+            kafka_ssl_cert_location: env::var("KAFKA_SSL_CERT_LOCATION").ok(),
+            kafka_ssl_key_location: env::var("KAFKA_SSL_KEY_LOCATION").ok(),
+            ingest_num_workers: env::var("INGEST_NUM_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(|| std::thread::available_parallelism().map(|p| p.get()).unwrap_or(4)),
+            elasticsearch_url: env::var("ELASTICSEARCH_URL")
+                .unwrap_or_else(|_| "http://localhost:9200".into()),
+            elasticsearch_index: env::var("ELASTICSEARCH_INDEX")
+                .unwrap_or_else(|_| "quiry-messages".into()),
+            elasticsearch_bulk_max_bytes: env::var("ELASTICSEARCH_BULK_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5 * 1024 * 1024),
+            elasticsearch_analyzer_profile: env::var("ELASTICSEARCH_ANALYZER_PROFILE")
+                .unwrap_or_else(|_| "english".into()),
+            vector_store_backend: env::var("VECTOR_STORE_BACKEND")
+                .unwrap_or_else(|_| "pinecone".into()),
+            hnsw_persist_path: env::var("HNSW_PERSIST_PATH")
+                .unwrap_or_else(|_| "hnsw_index.json".into()),
+            otlp_endpoint: env::var("OTLP_ENDPOINT").ok(),
+            otel_service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| "quiry".into()),
+            telegram_bot_token: env::var("TELEGRAM_BOT_TOKEN").ok(),
+            telegram_poll_timeout_secs: env::var("TELEGRAM_POLL_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+        }
+    }
+
+    /// Applies `security.protocol`/SASL/SSL settings to a Kafka `ClientConfig`,
+    /// so every consumer/producer built against `kafka_brokers` (not just the
+    /// one the bug was originally filed against) can talk to an authenticated
+    /// broker like Confluent Cloud or MSK. Leaves `client_config` untouched
+    /// beyond `security.protocol` when no SASL/SSL fields are set, matching
+    /// plaintext localhost Kafka.
+    pub fn apply_kafka_security(&self, client_config: &mut ClientConfig) {
+        client_config.set("security.protocol", &self.kafka_security_protocol);
+
+        if let Some(mechanism) = &self.kafka_sasl_mechanism {
+            client_config.set("sasl.mechanism", mechanism);
+        }
+        if let Some(username) = &self.kafka_sasl_username {
+            client_config.set("sasl.username", username);
+        }
+        if let Some(password) = &self.kafka_sasl_password {
+            client_config.set("sasl.password", password);
+        }
+        if let Some(ca_location) = &self.kafka_ssl_ca_location {
+            client_config.set("ssl.ca.location", ca_location);
+        }
+        if let Some(cert_location) = &self.kafka_ssl_cert_location {
+            client_config.set("ssl.certificate.location", cert_location);
+        }
+        if let Some(key_location) = &self.kafka_ssl_key_location {
+            client_config.set("ssl.key.location", key_location);
         }
     }
 }
@@ -0,0 +1,111 @@
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use serde::Serialize;
+use tracing::{info, error};
+use std::time::Duration;
+use crate::{
+    config::Config,
+    metrics::{DLQ_MESSAGES_TOTAL, DLQ_SEND_FAILURES_TOTAL},
+};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Whether a processing failure should be retried before landing in the
+/// DLQ. Invalid messages (bad JSON, a missing payload field) can never
+/// succeed on retry; transient failures (Cohere/Pinecone/ElasticSearch
+/// network errors) might.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Invalid,
+    Transient,
+}
+
+impl FailureKind {
+    fn as_label(&self) -> &'static str {
+        match self {
+            FailureKind::Invalid => "invalid",
+            FailureKind::Transient => "transient",
+        }
+    }
+}
+
+/// The JSON record written to `<topic><dlq_suffix>`: the original payload
+/// (best-effort decoded as UTF-8) plus enough metadata to triage and
+/// eventually replay it.
+#[derive(Debug, Serialize)]
+struct DeadLetter {
+    payload: String,
+    error: String,
+    kind: &'static str,
+    source_topic: String,
+    partition: i32,
+    offset: i64,
+    retry_count: u32,
+    failed_at: String,
+}
+
+/// Publishes un-processable Kafka messages to `<topic><dlq_suffix>`,
+/// modeled on Sentry's arroyo dead-letter pattern. Kept separate from
+/// `KafkaProducer` so a stalled DLQ never blocks the primary produce path.
+pub struct DlqProducer {
+    producer: FutureProducer,
+}
+
+impl DlqProducer {
+    pub fn new(cfg: &Config) -> Result<Self, DynErr> {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", &cfg.kafka_brokers)
+            .set("message.timeout.ms", "5000");
+        cfg.apply_kafka_security(&mut client_config);
+        let producer: FutureProducer = client_config.create()?;
+
+        Ok(Self { producer })
+    }
+
+    /// Sends `payload` (the raw bytes that failed to process) to
+    /// `<source_topic><dlq_suffix>` along with failure metadata, then
+    /// returns so the caller can advance past the offset regardless of
+    /// whether the DLQ publish itself succeeded.
+    pub async fn send(
+        &self,
+        cfg: &Config,
+        payload: &[u8],
+        kind: FailureKind,
+        error: &str,
+        source_topic: &str,
+        partition: i32,
+        offset: i64,
+        retry_count: u32,
+    ) -> Result<(), DynErr> {
+        let dlq_topic = format!("{}{}", source_topic, cfg.kafka_dlq_suffix);
+
+        let record = DeadLetter {
+            payload: String::from_utf8_lossy(payload).to_string(),
+            error: error.to_string(),
+            kind: kind.as_label(),
+            source_topic: source_topic.to_string(),
+            partition,
+            offset,
+            retry_count,
+            failed_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let body = serde_json::to_vec(&record)?;
+        let key = format!("{source_topic}-{partition}-{offset}");
+        let kafka_record = FutureRecord::to(&dlq_topic).key(&key).payload(&body);
+
+        match self.producer.send(kafka_record, Duration::from_secs(0)).await {
+            Ok(_) => {
+                info!(topic = %dlq_topic, partition, offset, retry_count, kind = record.kind, "Published message to DLQ");
+                DLQ_MESSAGES_TOTAL.with_label_values(&[source_topic, record.kind]).inc();
+                Ok(())
+            }
+            Err((kafka_error, _)) => {
+                error!(error = %kafka_error, topic = %dlq_topic, "Failed to publish message to DLQ");
+                DLQ_SEND_FAILURES_TOTAL.with_label_values(&[source_topic]).inc();
+                Err(kafka_error.into())
+            }
+        }
+    }
+}
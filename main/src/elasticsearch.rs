@@ -1,14 +1,132 @@
-use reqwest::Client;
 use serde_json::{json, Value};
 use tracing::{info, error, warn};
-use crate::{config::Config, schema::MessageEvent};
+use crate::{
+    config::Config,
+    schema::MessageEvent,
+    http_client::{shared_client, with_retry, RetryConfig, ELASTICSEARCH_BREAKER},
+    metrics::{ELASTICSEARCH_INDEX_DURATION, SEARCH_DURATION},
+    subscribe::INGESTION_SEQ,
+};
 
 type DynErr = Box<dyn std::error::Error + Send + Sync>;
 
 pub struct ElasticsearchClient {
-    client: Client,
     base_url: String,
     index_name: String,
+    analyzer_profile: AnalyzerProfile,
+}
+
+/// Selects how `create_index` builds the `text` field mapping and which
+/// sub-field(s) `search_messages` targets. Language profiles add the
+/// matching stemmer + stopword filters; `Cjk` tokenizes with `icu_tokenizer`
+/// (which needs the ICU analysis plugin) plus the `cjk_bigram` filter so
+/// Chinese/Japanese/Korean text splits into searchable units; `Autodetect`
+/// indexes `text` as a multi-field with several sub-analyzers at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyzerProfile {
+    English,
+    French,
+    German,
+    Spanish,
+    Cjk,
+    Autodetect,
+}
+
+impl AnalyzerProfile {
+    pub fn from_str(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "french" | "fr" => AnalyzerProfile::French,
+            "german" | "de" => AnalyzerProfile::German,
+            "spanish" | "es" => AnalyzerProfile::Spanish,
+            "cjk" => AnalyzerProfile::Cjk,
+            "autodetect" | "auto" => AnalyzerProfile::Autodetect,
+            _ => AnalyzerProfile::English,
+        }
+    }
+
+    fn as_meta_str(&self) -> &'static str {
+        match self {
+            AnalyzerProfile::English => "english",
+            AnalyzerProfile::French => "french",
+            AnalyzerProfile::German => "german",
+            AnalyzerProfile::Spanish => "spanish",
+            AnalyzerProfile::Cjk => "cjk",
+            AnalyzerProfile::Autodetect => "autodetect",
+        }
+    }
+
+    /// The language profiles that get their own sub-analyzer under
+    /// `Autodetect`, and that a single-language profile maps directly to.
+    fn language_profiles() -> [AnalyzerProfile; 4] {
+        [AnalyzerProfile::English, AnalyzerProfile::French, AnalyzerProfile::German, AnalyzerProfile::Spanish]
+    }
+
+    fn stopwords(&self) -> &'static str {
+        match self {
+            AnalyzerProfile::French => "_french_",
+            AnalyzerProfile::German => "_german_",
+            AnalyzerProfile::Spanish => "_spanish_",
+            _ => "_english_",
+        }
+    }
+
+    fn stemmer(&self) -> &'static str {
+        match self {
+            AnalyzerProfile::French => "french",
+            AnalyzerProfile::German => "light_german",
+            AnalyzerProfile::Spanish => "light_spanish",
+            _ => "english",
+        }
+    }
+
+    fn analyzer_name(&self) -> String {
+        format!("{}_analyzer", self.as_meta_str())
+    }
+
+    /// The ES analyzer definition for this profile's `analysis.analyzer` entry.
+    fn analyzer_definition(&self) -> Value {
+        match self {
+            AnalyzerProfile::Cjk => json!({
+                "tokenizer": "icu_tokenizer",
+                "filter": ["cjk_width", "cjk_bigram", "lowercase"]
+            }),
+            _ => json!({
+                "tokenizer": "standard",
+                "filter": ["lowercase", format!("{}_stop", self.as_meta_str()), format!("{}_stemmer", self.as_meta_str())]
+            }),
+        }
+    }
+
+    fn filter_definitions(&self) -> Value {
+        match self {
+            AnalyzerProfile::Cjk => json!({}),
+            _ => json!({
+                format!("{}_stop", self.as_meta_str()): {
+                    "type": "stop",
+                    "stopwords": self.stopwords()
+                },
+                format!("{}_stemmer", self.as_meta_str()): {
+                    "type": "stemmer",
+                    "language": self.stemmer()
+                }
+            }),
+        }
+    }
+
+    /// The `multi_match` fields `search_messages` should target for this
+    /// profile: `["text^2", "text.raw"]` for a single-language or CJK
+    /// profile, and a `text.<profile>^2` / `text.<profile>.raw` pair per
+    /// sub-analyzer (e.g. `text.french^2`) under `Autodetect`.
+    fn search_fields(&self) -> Vec<String> {
+        match self {
+            AnalyzerProfile::Autodetect => Self::language_profiles()
+                .iter()
+                .chain(std::iter::once(&AnalyzerProfile::Cjk))
+                .flat_map(|p| vec![format!("text.{}^2", p.as_meta_str()), format!("text.{}.raw", p.as_meta_str())])
+                .collect(),
+            _ => vec!["text^2".to_string(), "text.raw".to_string()],
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -19,41 +137,117 @@ pub struct ESQueryResult {
     pub timestamp: String,
     pub guild_id: Option<String>,
     pub score: f64,
+    /// Ingestion sequence number assigned at upsert time; see
+    /// `schema::QueryResult::seq`.
+    pub seq: u64,
 }
 
 impl ElasticsearchClient {
     pub async fn new(cfg: &Config) -> Result<Self, DynErr> {
-        let client = Client::new();
         let base_url = cfg.elasticsearch_url.clone();
         let index_name = cfg.elasticsearch_index.clone();
-        
+        let analyzer_profile = AnalyzerProfile::from_str(&cfg.elasticsearch_analyzer_profile);
+
         let es_client = Self {
-            client,
             base_url,
             index_name,
+            analyzer_profile,
         };
-        
+
         // Create index with proper mappings
         es_client.create_index().await?;
-        
+
         Ok(es_client)
     }
 
+    /// Builds the `text` field mapping for the configured analyzer profile:
+    /// a single custom analyzer for a language or CJK profile, or a
+    /// multi-field with one sub-analyzer per language (plus CJK) under
+    /// `Autodetect`.
+    fn text_field_mapping(&self) -> Value {
+        match self.analyzer_profile {
+            AnalyzerProfile::Autodetect => {
+                let mut fields = serde_json::Map::new();
+                for profile in AnalyzerProfile::language_profiles().iter().chain(std::iter::once(&AnalyzerProfile::Cjk)) {
+                    fields.insert(
+                        profile.as_meta_str().to_string(),
+                        json!({
+                            "type": "text",
+                            "analyzer": profile.analyzer_name(),
+                            "fields": { "raw": { "type": "keyword" } }
+                        }),
+                    );
+                }
+                json!({
+                    "type": "text",
+                    "analyzer": "standard",
+                    "fields": fields
+                })
+            }
+            profile => json!({
+                "type": "text",
+                "analyzer": profile.analyzer_name(),
+                "fields": {
+                    "raw": { "type": "keyword" }
+                }
+            }),
+        }
+    }
+
+    /// Builds the `settings.analysis` block containing the analyzer/filter
+    /// definitions referenced by `text_field_mapping`.
+    fn analysis_settings(&self) -> Value {
+        let language_profiles = AnalyzerProfile::language_profiles();
+        let profiles: Vec<&AnalyzerProfile> = match self.analyzer_profile {
+            AnalyzerProfile::Autodetect => language_profiles
+                .iter()
+                .chain(std::iter::once(&AnalyzerProfile::Cjk))
+                .collect(),
+            ref profile => vec![profile],
+        };
+
+        let mut analyzers = serde_json::Map::new();
+        let mut filters = serde_json::Map::new();
+        for profile in profiles {
+            analyzers.insert(profile.analyzer_name(), profile.analyzer_definition());
+            if let Value::Object(map) = profile.filter_definitions() {
+                filters.extend(map);
+            }
+        }
+
+        json!({ "analyzer": analyzers, "filter": filters })
+    }
+
     async fn create_index(&self) -> Result<(), DynErr> {
         let url = format!("{}/{}", self.base_url, self.index_name);
-        
+
         // Check if index exists
-        let response = self.client.head(&url).send().await?;
-        
+        let response = shared_client().get(&url).send().await?;
+
         if response.status().is_success() {
-            info!("ElasticSearch index already exists");
+            let existing: Value = response.json().await?;
+            let existing_profile = existing[self.index_name.as_str()]["mappings"]["_meta"]["analyzer_profile"]
+                .as_str()
+                .unwrap_or("unknown");
+            if existing_profile != self.analyzer_profile.as_meta_str() {
+                warn!(
+                    existing_profile,
+                    configured_profile = self.analyzer_profile.as_meta_str(),
+                    "ElasticSearch index exists with a different analyzer profile; mappings are immutable, reindex into a new index to switch"
+                );
+            } else {
+                info!("ElasticSearch index already exists");
+            }
             return Ok(());
         }
-        
-        info!("Creating ElasticSearch index: {}", self.index_name);
-        
+
+        info!("Creating ElasticSearch index: {} (analyzer profile: {})", self.index_name, self.analyzer_profile.as_meta_str());
+
         let body = json!({
             "mappings": {
+                "_meta": {
+                    "analyzer_profile": self.analyzer_profile.as_meta_str()
+                },
                 "properties": {
                     "message_id": {
                         "type": "keyword"
@@ -67,15 +261,7 @@ impl ElasticsearchClient {
                     "author_id": {
                         "type": "keyword"
                     },
-                    "text": {
-                        "type": "text",
-                        "analyzer": "standard",
-                        "fields": {
-                            "raw": {
-                                "type": "keyword"
-                            }
-                        }
-                    },
+                    "text": self.text_field_mapping(),
                     "timestamp": {
                         "type": "date",
                         "format": "strict_date_optional_time||epoch_millis"
@@ -83,24 +269,20 @@ impl ElasticsearchClient {
                     "created_at": {
                         "type": "date",
                         "format": "strict_date_optional_time||epoch_millis"
+                    },
+                    "seq": {
+                        "type": "long"
                     }
                 }
             },
             "settings": {
                 "number_of_shards": 1,
                 "number_of_replicas": 0,
-                "analysis": {
-                    "analyzer": {
-                        "standard": {
-                            "type": "standard",
-                            "stopwords": "_english_"
-                        }
-                    }
-                }
+                "analysis": self.analysis_settings()
             }
         });
 
-        let response = self.client
+        let response = shared_client()
             .put(&url)
             .json(&body)
             .send()
@@ -127,14 +309,13 @@ impl ElasticsearchClient {
             "author_id": message.author_id,
             "text": message.text,
             "timestamp": message.timestamp,
-            "created_at": chrono::Utc::now().to_rfc3339()
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "seq": INGESTION_SEQ.next_seq()
         });
 
-        let response = self.client
-            .put(&url)
-            .json(&doc)
-            .send()
-            .await?;
+        let response = with_retry(&ELASTICSEARCH_BREAKER, &ELASTICSEARCH_INDEX_DURATION, &RetryConfig::default(), || {
+            shared_client().put(&url).json(&doc).send()
+        }).await?;
 
         if response.status().is_success() {
             info!(message_id = %message.id, "Indexed message to ElasticSearch");
@@ -146,10 +327,70 @@ impl ElasticsearchClient {
         Ok(())
     }
 
+    /// Indexes a batch of messages via the `_bulk` NDJSON endpoint. Returns
+    /// the ids of any items the bulk response reports as failed, so a
+    /// partial failure doesn't need to fail the whole batch.
+    pub async fn bulk_index(&self, messages: &[MessageEvent]) -> Result<Vec<String>, DynErr> {
+        if messages.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let url = format!("{}/_bulk", self.base_url);
+
+        let mut body = String::new();
+        for message in messages {
+            let action = json!({ "index": { "_index": self.index_name, "_id": message.id } });
+            let doc = json!({
+                "message_id": message.id,
+                "guild_id": message.guild_id,
+                "channel_id": message.channel_id,
+                "author_id": message.author_id,
+                "text": message.text,
+                "timestamp": message.timestamp,
+                "created_at": chrono::Utc::now().to_rfc3339(),
+                "seq": INGESTION_SEQ.next_seq()
+            });
+            body.push_str(&action.to_string());
+            body.push('\n');
+            body.push_str(&doc.to_string());
+            body.push('\n');
+        }
+
+        let response = with_retry(&ELASTICSEARCH_BREAKER, &ELASTICSEARCH_INDEX_DURATION, &RetryConfig::default(), || {
+            shared_client()
+                .post(&url)
+                .header("Content-Type", "application/x-ndjson")
+                .body(body.clone())
+                .send()
+        }).await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            error!(count = messages.len(), "ElasticSearch bulk index request failed: {}", error_text);
+            return Err("ElasticSearch bulk index request failed".into());
+        }
+
+        let response_body: Value = response.json().await?;
+        let empty_vec = vec![];
+        let items = response_body["items"].as_array().unwrap_or(&empty_vec);
+
+        let mut failed_ids = Vec::new();
+        for (item, message) in items.iter().zip(messages.iter()) {
+            let status = item["index"]["status"].as_u64().unwrap_or(0);
+            if !(200..300).contains(&status) {
+                warn!(message_id = %message.id, status, "Failed to bulk index message");
+                failed_ids.push(message.id.clone());
+            }
+        }
+
+        info!(count = messages.len(), failed = failed_ids.len(), "Bulk indexed messages to ElasticSearch");
+        Ok(failed_ids)
+    }
+
     pub async fn delete_message(&self, message_id: &str) -> Result<(), DynErr> {
         let url = format!("{}/{}/_doc/{}", self.base_url, self.index_name, message_id);
         
-        let response = self.client
+        let response = shared_client()
             .delete(&url)
             .send()
             .await?;
@@ -180,7 +421,7 @@ impl ElasticsearchClient {
             json!({
                 "multi_match": {
                     "query": query,
-                    "fields": ["text^2", "text.raw"],
+                    "fields": self.analyzer_profile.search_fields(),
                     "type": "best_fields",
                     "fuzziness": "AUTO"
                 }
@@ -225,11 +466,9 @@ impl ElasticsearchClient {
             ]
         });
 
-        let response = self.client
-            .post(&url)
-            .json(&search_body)
-            .send()
-            .await?;
+        let response = with_retry(&ELASTICSEARCH_BREAKER, &SEARCH_DURATION, &RetryConfig::default(), || {
+            shared_client().post(&url).json(&search_body).send()
+        }).await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
@@ -258,6 +497,7 @@ impl ElasticsearchClient {
                     timestamp: timestamp.to_string(),
                     guild_id: source["guild_id"].as_str().map(|s| s.to_string()),
                     score,
+                    seq: source["seq"].as_u64().unwrap_or(0),
                 });
             }
         }
@@ -266,10 +506,139 @@ impl ElasticsearchClient {
         Ok(results)
     }
 
+    /// Pages through stored messages ordered by timestamp for the `/history`
+    /// command, mirroring IRC's CHATHISTORY semantics. `Latest` and `Before`
+    /// page backward (sorted `desc`); `After` pages forward (sorted `asc`,
+    /// then reversed to match the newest-first convention of the other
+    /// selectors); `Around` runs a `Before`-style and an `After`-style
+    /// window on either side of the pivot and merges them.
+    pub async fn query_history(
+        &self,
+        selector: &crate::schema::HistorySelector,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+        author_id: Option<&str>,
+        limit: usize,
+    ) -> Result<crate::schema::HistoryPage, DynErr> {
+        use crate::schema::{HistoryPage, HistorySelector};
+
+        let messages = match selector {
+            HistorySelector::Latest => {
+                self.history_window(guild_id, channel_id, author_id, None, limit, "desc").await?
+            }
+            HistorySelector::Before(ts) => {
+                self.history_window(guild_id, channel_id, author_id, Some(("lt", ts.as_str())), limit, "desc").await?
+            }
+            HistorySelector::After(ts) => {
+                let mut messages = self.history_window(guild_id, channel_id, author_id, Some(("gt", ts.as_str())), limit, "asc").await?;
+                messages.reverse();
+                messages
+            }
+            HistorySelector::Around(ts) => {
+                let half = (limit / 2).max(1);
+                let before = self.history_window(guild_id, channel_id, author_id, Some(("lt", ts.as_str())), half, "desc").await?;
+                let after = self.history_window(guild_id, channel_id, author_id, Some(("gte", ts.as_str())), limit - half, "asc").await?;
+                let mut messages = before;
+                messages.extend(after);
+                messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+                messages.truncate(limit);
+                messages
+            }
+        };
+
+        // `messages` is always newest-first, but the next page continues in
+        // the direction the caller is paging: `After` pages forward, so its
+        // cursor must be the *newest* boundary in this page (`first()`) -
+        // `last()` would be the oldest row already shown and `gt` that
+        // re-returns almost the whole window. The backward selectors
+        // (`Latest`/`Before`/`Around`) continue with the oldest boundary.
+        let cursor = match selector {
+            HistorySelector::After(_) => messages.first().map(|msg| msg.timestamp.clone()),
+            _ => messages.last().map(|msg| msg.timestamp.clone()),
+        };
+        Ok(crate::schema::HistoryPage { messages, cursor })
+    }
+
+    /// One timestamp-sorted window of messages: `range` is an optional
+    /// `(comparison operator, boundary timestamp)` pair, e.g. `("lt", ts)`
+    /// for "strictly before `ts`"; `None` fetches the whole (filtered) index.
+    async fn history_window(
+        &self,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+        author_id: Option<&str>,
+        range: Option<(&str, &str)>,
+        limit: usize,
+        sort_order: &str,
+    ) -> Result<Vec<crate::schema::HistoryMessage>, DynErr> {
+        let url = format!("{}/{}/_search", self.base_url, self.index_name);
+
+        let mut must_clauses = Vec::new();
+        if let Some(guild_id) = guild_id {
+            must_clauses.push(json!({ "term": { "guild_id": guild_id } }));
+        }
+        if let Some(channel_id) = channel_id {
+            must_clauses.push(json!({ "term": { "channel_id": channel_id } }));
+        }
+        if let Some(author_id) = author_id {
+            must_clauses.push(json!({ "term": { "author_id": author_id } }));
+        }
+        if let Some((op, ts)) = range {
+            let mut bound = serde_json::Map::new();
+            bound.insert(op.to_string(), json!(ts));
+            must_clauses.push(json!({ "range": { "timestamp": bound } }));
+        }
+
+        let query = if must_clauses.is_empty() {
+            json!({ "match_all": {} })
+        } else {
+            json!({ "bool": { "must": must_clauses } })
+        };
+
+        let search_body = json!({
+            "query": query,
+            "size": limit,
+            "sort": [{ "timestamp": { "order": sort_order } }]
+        });
+
+        let response = with_retry(&ELASTICSEARCH_BREAKER, &SEARCH_DURATION, &RetryConfig::default(), || {
+            shared_client().post(&url).json(&search_body).send()
+        }).await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("ElasticSearch history query failed: {}", error_text).into());
+        }
+
+        let response_body: Value = response.json().await?;
+        let empty_vec = vec![];
+        let hits = response_body["hits"]["hits"].as_array().unwrap_or(&empty_vec);
+
+        let mut messages = Vec::new();
+        for hit in hits {
+            let source = &hit["_source"];
+            if let (Some(text), Some(author_id), Some(channel_id), Some(timestamp)) = (
+                source["text"].as_str(),
+                source["author_id"].as_str(),
+                source["channel_id"].as_str(),
+                source["timestamp"].as_str(),
+            ) {
+                messages.push(crate::schema::HistoryMessage {
+                    text: text.to_string(),
+                    author_id: author_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    timestamp: timestamp.to_string(),
+                });
+            }
+        }
+
+        Ok(messages)
+    }
+
     pub async fn health_check(&self) -> Result<bool, DynErr> {
         let url = format!("{}/_cluster/health", self.base_url);
         
-        let response = self.client
+        let response = shared_client()
             .get(&url)
             .send()
             .await?;
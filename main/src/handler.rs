@@ -1,23 +1,29 @@
+use futures::StreamExt;
 use serenity::{
     async_trait,
     model::{channel::Message, gateway::Ready},
     prelude::*,
-    builder::{CreateCommand, CreateCommandOption},
-    all::{CreateInteractionResponse, CreateInteractionResponseMessage, CreateInteractionResponseFollowup, Interaction, CommandOptionType},
+    builder::{CreateCommand, CreateCommandOption, EditMessage},
+    all::{CreateInteractionResponse, CreateInteractionResponseMessage, CreateInteractionResponseFollowup, Interaction, CommandOptionType, UserId},
 };
 use tracing::{info, error, warn};
 use tokio::sync::Mutex;
 use crate::{
     config::Config,
-    schema::MessageEvent,
-    cohere::{get_embedding, generate_response, generate_response_from_chunks},
-    pinecone::{upsert_to_pinecone, query_pinecone, query_chunks_pinecone},
+    schema::{MessageEvent, HistorySelector, HistoryPage, Platform},
+    cohere::{get_embedding, generate_response, generate_response_from_chunks, generate_response_from_chunks_streamed, EmbeddingInputType},
+    pinecone::{upsert_to_pinecone, query_pinecone, query_chunks_pinecone, query_history_pinecone},
     chunking::ChunkManager,
     kafka_producer::KafkaProducer,
     kafka_types::KafkaMessage,
     elasticsearch::ElasticsearchClient,
 };
 
+/// How often `handle_ask_command_streamed` edits the `/ask` followup message
+/// as Cohere's stream delivers tokens. Frequent enough to feel live, coarse
+/// enough to stay well clear of Discord's per-message edit rate limit.
+const ASK_STREAM_EDIT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(750);
+
 pub struct Handler {
     pub cfg: Config,
     pub chunk_manager: Mutex<ChunkManager>,
@@ -50,12 +56,51 @@ impl EventHandler for Handler {
             .add_option(
                 CreateCommandOption::new(CommandOptionType::User, "author", "Filter by specific user (optional)")
                     .required(false)
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "platform", "Restrict search to one platform (optional)")
+                    .required(false)
+                    .add_string_choice("discord", "discord")
+                    .add_string_choice("telegram", "telegram")
+                    .add_string_choice("bridge", "bridge")
             );
         if let Err(err) = ctx.http.create_global_command(&ask_cmd).await {
             error!("Failed to register global /ask: {err:?}");
         } else {
             info!("Global slash command /ask registered.");
         }
+
+        let history_cmd = CreateCommand::new("history")
+            .description("Page through stored conversation history (CHATHISTORY-style)")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "selector", "Which window of history to fetch")
+                    .required(true)
+                    .add_string_choice("latest", "latest")
+                    .add_string_choice("before", "before")
+                    .add_string_choice("after", "after")
+                    .add_string_choice("around", "around")
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "timestamp", "RFC3339 boundary timestamp (required for before/after/around)")
+                    .required(false)
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "limit", "Max messages to return (default 20)")
+                    .required(false)
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "channel", "Filter by channel name (optional)")
+                    .required(false)
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "author", "Filter by specific user (optional)")
+                    .required(false)
+            );
+        if let Err(err) = ctx.http.create_global_command(&history_cmd).await {
+            error!("Failed to register global /history: {err:?}");
+        } else {
+            info!("Global slash command /history registered.");
+        }
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
@@ -74,7 +119,8 @@ impl EventHandler for Handler {
                     let mut question = None;
                     let mut channel_filter = None;
                     let mut author_filter = None;
-                    
+                    let mut platform_filter = None;
+
                     for option in &command.data.options {
                         match option.name.as_str() {
                             "question" => {
@@ -92,13 +138,18 @@ impl EventHandler for Handler {
                                     author_filter = Some(value.to_string());
                                 }
                             }
+                            "platform" => {
+                                if let Some(value) = option.value.as_str() {
+                                    platform_filter = parse_platform(value);
+                                }
+                            }
                             _ => {}
                         }
                     }
 
                     if let Some(question) = question {
-                        info!("Processing /ask question: {} (channel: {:?}, author: {:?})", 
-                              question, channel_filter, author_filter);
+                        info!("Processing /ask question: {} (channel: {:?}, author: {:?}, platform: {:?})",
+                              question, channel_filter, author_filter, platform_filter);
 
                         let initial_resp = CreateInteractionResponse::Message(
                             CreateInteractionResponseMessage::new().content("🔍 Searching for relevant messages..."),
@@ -109,21 +160,110 @@ impl EventHandler for Handler {
                         }
 
                         let guild_id = command.guild_id.map(|id| id.to_string());
-                        match self.handle_ask_command_with_filters(question, guild_id, channel_filter, author_filter).await {
-                            Ok(response) => {
-                                let followup = CreateInteractionResponseFollowup::new().content(response);
-                                if let Err(err) = command.create_followup(&ctx.http, followup).await {
-                                    error!("Cannot send followup response: {err:?}");
-                                }
+                        let followup = CreateInteractionResponseFollowup::new().content("🤖 Thinking...");
+                        match command.create_followup(&ctx.http, followup).await {
+                            Ok(message) => {
+                                self.handle_ask_command_streamed(
+                                    &ctx,
+                                    message,
+                                    question,
+                                    guild_id,
+                                    channel_filter,
+                                    author_filter,
+                                    platform_filter,
+                                ).await;
                             }
                             Err(err) => {
-                                error!("Failed to process /ask: {err}");
-                                let error_resp = CreateInteractionResponseFollowup::new()
-                                    .content("Sorry, I encountered an error while processing your question.");
-                                if let Err(err) = command.create_followup(&ctx.http, error_resp).await {
-                                    error!("Cannot send error response: {err:?}");
+                                error!("Cannot send initial followup: {err:?}");
+                            }
+                        }
+                    }
+                }
+                "history" => {
+                    let mut selector_str = None;
+                    let mut timestamp = None;
+                    let mut limit: i64 = 20;
+                    let mut channel_filter = None;
+                    let mut author_filter = None;
+
+                    for option in &command.data.options {
+                        match option.name.as_str() {
+                            "selector" => {
+                                if let Some(value) = option.value.as_str() {
+                                    selector_str = Some(value);
+                                }
+                            }
+                            "timestamp" => {
+                                if let Some(value) = option.value.as_str() {
+                                    timestamp = Some(value);
+                                }
+                            }
+                            "limit" => {
+                                if let Some(value) = option.value.as_i64() {
+                                    limit = value;
+                                }
+                            }
+                            "channel" => {
+                                if let Some(value) = option.value.as_str() {
+                                    channel_filter = Some(value);
                                 }
                             }
+                            "author" => {
+                                if let Some(value) = option.value.as_user_id() {
+                                    author_filter = Some(value.to_string());
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let selector = match (selector_str, timestamp) {
+                        (Some("latest"), _) => Some(HistorySelector::Latest),
+                        (Some("before"), Some(ts)) => Some(HistorySelector::Before(ts.to_string())),
+                        (Some("after"), Some(ts)) => Some(HistorySelector::After(ts.to_string())),
+                        (Some("around"), Some(ts)) => Some(HistorySelector::Around(ts.to_string())),
+                        _ => None,
+                    };
+
+                    let Some(selector) = selector else {
+                        let resp = CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .content("`before`/`after`/`around` need a `timestamp` option."),
+                        );
+                        if let Err(err) = command.create_response(&ctx.http, resp).await {
+                            error!("Cannot respond to /history: {err:?}");
+                        }
+                        return;
+                    };
+
+                    let limit = limit.clamp(1, 100) as usize;
+
+                    info!("Processing /history selector: {:?} (channel: {:?}, author: {:?}, limit: {})",
+                          selector, channel_filter, author_filter, limit);
+
+                    let initial_resp = CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new().content("📜 Fetching message history..."),
+                    );
+                    if let Err(err) = command.create_response(&ctx.http, initial_resp).await {
+                        error!("Cannot send initial response: {err:?}");
+                        return;
+                    }
+
+                    let guild_id = command.guild_id.map(|id| id.to_string());
+                    match self.handle_history_command(&ctx, selector, guild_id, channel_filter, author_filter, limit).await {
+                        Ok(response) => {
+                            let followup = CreateInteractionResponseFollowup::new().content(response);
+                            if let Err(err) = command.create_followup(&ctx.http, followup).await {
+                                error!("Cannot send followup response: {err:?}");
+                            }
+                        }
+                        Err(err) => {
+                            error!("Failed to process /history: {err}");
+                            let error_resp = CreateInteractionResponseFollowup::new()
+                                .content("Sorry, I encountered an error while fetching message history.");
+                            if let Err(err) = command.create_followup(&ctx.http, error_resp).await {
+                                error!("Cannot send error response: {err:?}");
+                            }
                         }
                     }
                 }
@@ -137,6 +277,7 @@ impl EventHandler for Handler {
 
         let event = MessageEvent {
             id: msg.id.to_string(),
+            platform: Platform::Discord,
             guild_id: msg.guild_id.map(|id| id.to_string()),
             channel_id: msg.channel_id.to_string(),
             author_id: msg.author.id.to_string(),
@@ -195,42 +336,51 @@ impl Handler {
         }
     }
 
-    async fn hybrid_search(
+    /// Runs the Pinecone (semantic) + optional Elasticsearch (keyword)
+    /// retrieval `/ask` grounds its answer on, fusing both with
+    /// `merge_search_results` and reshaping the fused list into
+    /// `ChunkQueryResult`s so callers don't care whether a given chunk came
+    /// from Pinecone or Elasticsearch. Returns an empty `Vec` rather than an
+    /// error when nothing matches, including when no ES client is
+    /// configured - that's a normal "Pinecone-only" search, not a failure.
+    async fn fetch_ask_context(
         &self,
         query: &str,
         guild_id: Option<&str>,
         channel_id: Option<&str>,
         author_id: Option<&str>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Get Pinecone results (semantic search)
+        platform: Option<Platform>,
+    ) -> Result<Vec<crate::schema::ChunkQueryResult>, Box<dyn std::error::Error + Send + Sync>> {
         let pinecone_results = if let Some(guild_id) = guild_id {
-            let embedding = get_embedding(&self.cfg, query).await?;
+            let embedding = get_embedding(&self.cfg, query, EmbeddingInputType::SearchQuery).await?;
+            let mut filters = crate::schema::QueryFilters::builder();
+            if let Some(channel_id) = channel_id {
+                filters = filters.channel_ids(vec![channel_id.to_string()]);
+            }
+            if let Some(author_id) = author_id {
+                filters = filters.author_id(author_id);
+            }
             query_chunks_pinecone(
                 &self.cfg,
                 embedding,
                 5,
                 Some(guild_id.to_string()),
+                platform,
+                &filters.build(),
             ).await?
         } else {
             vec![]
         };
 
-        // Get ElasticSearch results (keyword search)
         let es_results = if let Some(ref es_client) = self.es_client {
             es_client.search_messages(query, guild_id, channel_id, author_id, 5).await?
         } else {
             vec![]
         };
 
-        // Combine and merge results
         let combined_results = self.merge_search_results(pinecone_results, es_results, 0.65).await?;
-        
-        if combined_results.is_empty() {
-            return Ok("I couldn't find any relevant information about that topic.".to_string());
-        }
 
-        // Generate response from combined results
-        let context_chunks: Vec<crate::schema::ChunkQueryResult> = combined_results.iter()
+        Ok(combined_results.iter()
             .map(|result| crate::schema::ChunkQueryResult {
                 chunk_id: result.text.clone(),
                 text: result.text.clone(),
@@ -241,11 +391,16 @@ impl Handler {
                 last_timestamp: result.timestamp.clone(),
                 score: result.score,
             })
-            .collect();
-
-        generate_response_from_chunks(&self.cfg, query, &context_chunks).await
+            .collect())
     }
 
+    /// Fuses the Pinecone (semantic) and Elasticsearch (lexical) result
+    /// lists with Reciprocal Rank Fusion instead of hand-normalizing two
+    /// incompatible score scales: `score(d) = Σ_l weight_l / (k + rank_l(d))`,
+    /// summed only over the lists that contain `d`, mirroring
+    /// [`crate::search::hybrid_search`]. `alpha` is kept as a list-weight
+    /// multiplier (Pinecone gets `alpha`, Elasticsearch gets `1.0 - alpha`)
+    /// for backward compatibility with existing callers.
     async fn merge_search_results(
         &self,
         pinecone_results: Vec<crate::schema::ChunkQueryResult>,
@@ -253,46 +408,68 @@ impl Handler {
         alpha: f64,
     ) -> Result<Vec<crate::elasticsearch::ESQueryResult>, Box<dyn std::error::Error + Send + Sync>> {
         use std::collections::HashMap;
-        
-        let mut combined_scores: HashMap<String, (f64, crate::elasticsearch::ESQueryResult)> = HashMap::new();
-        
-        // Add Pinecone results (normalize scores to 0-1)
-        for result in pinecone_results {
-            let normalized_score = (result.score + 1.0) / 2.0; // Convert from [-1,1] to [0,1]
-            let final_score = alpha * normalized_score;
-            
-            let es_result = crate::elasticsearch::ESQueryResult {
+
+        const RRF_K: f64 = 60.0;
+        let pinecone_weight = alpha;
+        let es_weight = 1.0 - alpha;
+
+        fn normalized_text_key(text: &str) -> String {
+            text.trim().to_lowercase()
+        }
+
+        // (fused score, richest metadata seen so far, best original rank
+        // across lists - used only to break score ties deterministically).
+        let mut fused: HashMap<String, (f64, crate::elasticsearch::ESQueryResult, usize)> = HashMap::new();
+
+        for (rank, result) in pinecone_results.into_iter().enumerate() {
+            // Keyed on normalized text, not `chunk_id`: Elasticsearch results
+            // have no chunk id to compare against, so a shared id would
+            // never match across lists and the same item surfaced by both
+            // retrievers would never accumulate both RRF terms.
+            let key = normalized_text_key(&result.text);
+            let contribution = pinecone_weight / (RRF_K + (rank + 1) as f64);
+
+            let placeholder = crate::elasticsearch::ESQueryResult {
                 text: result.text.clone(),
-                author_id: result.authors.first().unwrap_or(&"unknown".to_string()).clone(),
+                author_id: result.authors.first().cloned().unwrap_or_else(|| "unknown".to_string()),
                 channel_id: "unknown".to_string(), // ChunkQueryResult doesn't have channel_id
                 timestamp: result.first_timestamp.clone(),
                 guild_id: None, // ChunkQueryResult doesn't have guild_id
-                score: final_score,
+                score: 0.0,
+                seq: 0, // chunks aren't ingestion-seq tracked
             };
-            
-            combined_scores.insert(result.chunk_id, (final_score, es_result));
+
+            let entry = fused.entry(key).or_insert((0.0, placeholder, rank));
+            entry.0 += contribution;
+            entry.2 = entry.2.min(rank);
         }
-        
-        // Add ElasticSearch results
-        for result in es_results {
-            let normalized_score = result.score / 10.0; // Rough normalization
-            let final_score = (1.0 - alpha) * normalized_score;
-            
-            if let Some((existing_score, _)) = combined_scores.get(&result.text) {
-                // If we have both Pinecone and ES results for the same content, take the max
-                if final_score > *existing_score {
-                    combined_scores.insert(result.text.clone(), (final_score, result));
-                }
-            } else {
-                combined_scores.insert(result.text.clone(), (final_score, result));
+
+        for (rank, result) in es_results.into_iter().enumerate() {
+            let key = normalized_text_key(&result.text);
+            let contribution = es_weight / (RRF_K + (rank + 1) as f64);
+
+            let entry = fused.entry(key).or_insert_with(|| (0.0, result.clone(), rank));
+            entry.0 += contribution;
+            entry.2 = entry.2.min(rank);
+            // Elasticsearch's own record carries real channel/guild/seq
+            // metadata, so it wins over the Pinecone placeholder above
+            // once both lists surface the same content.
+            if entry.1.channel_id == "unknown" {
+                entry.1 = result;
             }
         }
-        
-        // Sort by combined score and return top results
-        let mut results: Vec<_> = combined_scores.into_values().collect();
-        results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        
-        Ok(results.into_iter().map(|(_, result)| result).collect())
+
+        let mut results: Vec<_> = fused.into_values().collect();
+        results.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.2.cmp(&b.2))
+        });
+
+        Ok(results.into_iter().map(|(score, mut result, _)| {
+            result.score = score;
+            result
+        }).collect())
     }
 
     async fn process_message_directly(&self, event: MessageEvent) {
@@ -303,7 +480,7 @@ impl Handler {
         }
 
         // Keep individual message processing as fallback/compatibility
-        match get_embedding(&self.cfg, &event.text).await {
+        match get_embedding(&self.cfg, &event.text, EmbeddingInputType::SearchDocument).await {
             Ok(embedding) => {
                 if let Err(err) = upsert_to_pinecone(&self.cfg, &event, embedding).await {
                     error!("Failed to upsert individual message: {err}");
@@ -313,41 +490,108 @@ impl Handler {
         }
     }
 
-    async fn handle_ask_command_with_filters(
-        &self, 
-        question: &str, 
+    /// Backs `/ask`: fetches grounding context via `fetch_ask_context`, then
+    /// streams Cohere's answer into `message` (the already-created
+    /// followup), editing it roughly every `ASK_STREAM_EDIT_INTERVAL` as
+    /// tokens arrive so the reply appears to type itself out instead of
+    /// sitting on "🤖 Thinking..." for the several seconds a full completion
+    /// takes. `fetch_ask_context` only applies `platform_filter` to the
+    /// Pinecone leg; ES has no platform field indexed yet, so a
+    /// platform-scoped /ask against an ES-backed deployment still sees ES's
+    /// unfiltered keyword matches. Falls back to the non-streaming,
+    /// message-level `handle_ask_command` when no chunk context is found.
+    async fn handle_ask_command_streamed(
+        &self,
+        ctx: &Context,
+        mut message: Message,
+        question: &str,
         guild_id: Option<String>,
         channel_filter: Option<&str>,
         author_filter: Option<String>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Initialize ES client if not already done
-        if self.es_client.is_none() {
-            // Note: This is a simplified approach. In production, you'd want to handle this more carefully
-            // to avoid race conditions and ensure proper initialization
-            info!("ElasticSearch client not initialized, using Pinecone-only search");
+        platform_filter: Option<Platform>,
+    ) {
+        let context_chunks = match self.fetch_ask_context(
+            question,
+            guild_id.as_deref(),
+            channel_filter,
+            author_filter.as_deref(),
+            platform_filter,
+        ).await {
+            Ok(chunks) => chunks,
+            Err(err) => {
+                error!("Failed to fetch /ask context: {err}");
+                self.edit_ask_message(ctx, &mut message, "Sorry, I encountered an error while processing your question.").await;
+                return;
+            }
+        };
+
+        if context_chunks.is_empty() {
+            let response = match self.handle_ask_command(question, guild_id, platform_filter).await {
+                Ok(response) => response,
+                Err(err) => {
+                    error!("Failed to process /ask: {err}");
+                    "Sorry, I encountered an error while processing your question.".to_string()
+                }
+            };
+            self.edit_ask_message(ctx, &mut message, &response).await;
+            return;
+        }
+
+        let mut stream = match generate_response_from_chunks_streamed(&self.cfg, question, &context_chunks).await {
+            Ok(stream) => Box::pin(stream),
+            Err(err) => {
+                error!("Failed to start Cohere stream for /ask: {err}");
+                self.edit_ask_message(ctx, &mut message, "Sorry, I encountered an error while processing your question.").await;
+                return;
+            }
+        };
+
+        let mut buffer = String::new();
+        let mut last_edit = tokio::time::Instant::now();
+
+        while let Some(token) = stream.next().await {
+            match token {
+                Ok(delta) => {
+                    buffer.push_str(&delta);
+                    if last_edit.elapsed() >= ASK_STREAM_EDIT_INTERVAL {
+                        self.edit_ask_message(ctx, &mut message, &format!("{buffer}\u{258c}")).await;
+                        last_edit = tokio::time::Instant::now();
+                    }
+                }
+                Err(err) => {
+                    error!("Cohere stream for /ask failed mid-response: {err}");
+                    let content = if buffer.is_empty() {
+                        "Sorry, I encountered an error while processing your question.".to_string()
+                    } else {
+                        buffer.clone()
+                    };
+                    self.edit_ask_message(ctx, &mut message, &content).await;
+                    return;
+                }
+            }
         }
 
-        // Use hybrid search if ES is available, otherwise fallback to Pinecone-only
-        if let Some(ref _es_client) = self.es_client {
-            self.hybrid_search(
-                question,
-                guild_id.as_deref(),
-                channel_filter,
-                author_filter.as_deref(),
-            ).await
+        let final_text = if buffer.is_empty() {
+            "I couldn't find any relevant information about that topic.".to_string()
         } else {
-            // Fallback to original Pinecone-only search
-            self.handle_ask_command(question, guild_id).await
+            buffer
+        };
+        self.edit_ask_message(ctx, &mut message, &final_text).await;
+    }
+
+    async fn edit_ask_message(&self, ctx: &Context, message: &mut Message, content: &str) {
+        if let Err(err) = message.edit(&ctx.http, EditMessage::new().content(content)).await {
+            error!("Cannot edit /ask response: {err:?}");
         }
     }
 
-    async fn handle_ask_command(&self, question: &str, guild_id: Option<String>) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    async fn handle_ask_command(&self, question: &str, guild_id: Option<String>, platform: Option<Platform>) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         info!("Getting embedding for question: {}", question);
-        let question_embedding = get_embedding(&self.cfg, question).await?;
+        let question_embedding = get_embedding(&self.cfg, question, EmbeddingInputType::SearchQuery).await?;
 
         // First try to query chunks
         info!("Querying Pinecone for similar chunks in guild: {:?}", guild_id);
-        let similar_chunks = query_chunks_pinecone(&self.cfg, question_embedding.clone(), 3, guild_id.clone()).await?;
+        let similar_chunks = query_chunks_pinecone(&self.cfg, question_embedding.clone(), 3, guild_id.clone(), platform, &crate::schema::QueryFilters::default()).await?;
 
         if !similar_chunks.is_empty() {
             info!("Found {} similar chunks, generating response", similar_chunks.len());
@@ -357,7 +601,7 @@ impl Handler {
 
         // Fallback to individual messages
         info!("No chunks found, querying individual messages in guild: {:?}", guild_id);
-        let similar_messages = query_pinecone(&self.cfg, question_embedding, 5, guild_id).await?;
+        let similar_messages = query_pinecone(&self.cfg, question_embedding, 5, guild_id, platform, &crate::schema::QueryFilters::default()).await?;
 
         if similar_messages.is_empty() {
             return Ok("I couldn't find any relevant messages in the history to answer your question.".to_string());
@@ -368,4 +612,87 @@ impl Handler {
 
         Ok(response)
     }
+
+    /// Backs `/history`: a plain browse of stored messages, not an
+    /// LLM-generated answer like `/ask`. Prefers ElasticSearch (native
+    /// timestamp sort); falls back to `query_history_pinecone`'s
+    /// client-side windowing when no ES client is configured.
+    async fn handle_history_command(
+        &self,
+        ctx: &Context,
+        selector: HistorySelector,
+        guild_id: Option<String>,
+        channel_filter: Option<&str>,
+        author_filter: Option<String>,
+        limit: usize,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let page: HistoryPage = if let Some(ref es_client) = self.es_client {
+            es_client.query_history(&selector, guild_id.as_deref(), channel_filter, author_filter.as_deref(), limit).await?
+        } else {
+            query_history_pinecone(&self.cfg, &selector, guild_id, channel_filter, author_filter.as_deref(), limit).await?
+        };
+
+        if page.messages.is_empty() {
+            return Ok("No messages found in that window.".to_string());
+        }
+
+        Ok(self.render_history_page(ctx, &selector, &page).await)
+    }
+
+    /// Renders a [`HistoryPage`] as Markdown, following the same no-raw-ID /
+    /// no-@mention rules `generate_response_from_chunks` encodes for the
+    /// LLM-generated `/ask` path: author ids are resolved to plain display
+    /// names, never left as snowflakes or turned into pings.
+    async fn render_history_page(&self, ctx: &Context, selector: &HistorySelector, page: &HistoryPage) -> String {
+        let mut names: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        let mut lines = Vec::with_capacity(page.messages.len());
+
+        for msg in &page.messages {
+            let name = if let Some(name) = names.get(&msg.author_id) {
+                name.clone()
+            } else {
+                let resolved = self.resolve_display_name(ctx, &msg.author_id).await;
+                names.insert(msg.author_id.clone(), resolved.clone());
+                resolved
+            };
+            lines.push(format!("**{}** ({}): {}", name, msg.timestamp, msg.text));
+        }
+
+        let mut rendered = lines.join("\n");
+
+        if let Some(cursor) = &page.cursor {
+            let next_page_hint = match selector {
+                HistorySelector::After(_) => format!("selector:after timestamp:{cursor}"),
+                _ => format!("selector:before timestamp:{cursor}"),
+            };
+            rendered.push_str(&format!("\n\n*Use `/history {next_page_hint}` for the next page.*"));
+        }
+
+        rendered
+    }
+
+    /// Resolves a Discord user id to a plain display name, falling back to
+    /// a generic label (never the raw snowflake) if the lookup fails.
+    async fn resolve_display_name(&self, ctx: &Context, author_id: &str) -> String {
+        match author_id.parse::<u64>() {
+            Ok(id) => match UserId::new(id).to_user(&ctx.http).await {
+                Ok(user) => user.name,
+                Err(_) => "a participant".to_string(),
+            },
+            Err(_) => "a participant".to_string(),
+        }
+    }
+}
+
+/// Parses the `/ask platform` option's string choice into a [`Platform`].
+/// Unrecognized values are treated as "no filter" rather than an error,
+/// since this only ever sees values from the fixed Discord string choices
+/// registered on the command above.
+fn parse_platform(value: &str) -> Option<Platform> {
+    match value {
+        "discord" => Some(Platform::Discord),
+        "telegram" => Some(Platform::Telegram),
+        "bridge" => Some(Platform::Bridge),
+        _ => None,
+    }
 }
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -15,11 +16,66 @@ pub struct ServiceHealth {
     pub response_time_ms: Option<u64>,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadinessStatus {
+    pub ready: bool,
+    pub has_partition_assignment: bool,
+    pub last_poll_age_secs: Option<f64>,
+}
+
+/// Tracks whether `KafkaConsumer` currently holds a partition assignment and
+/// when it last polled successfully. Set from `ReadinessContext`'s
+/// pre/post-rebalance callbacks and from `start_consuming`'s loop, read by
+/// `HealthChecker::get_readiness`. Uses a plain `std::sync::Mutex` rather
+/// than `tokio::sync::Mutex` because the rebalance callbacks run
+/// synchronously on librdkafka's own thread, not inside an async task.
+#[derive(Clone)]
+pub struct ConsumerReadiness {
+    has_assignment: Arc<std::sync::Mutex<bool>>,
+    last_poll_at: Arc<std::sync::Mutex<Option<Instant>>>,
+}
+
+impl ConsumerReadiness {
+    pub fn new() -> Self {
+        Self {
+            has_assignment: Arc::new(std::sync::Mutex::new(false)),
+            last_poll_at: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    pub fn set_assigned(&self, assigned: bool) {
+        *self.has_assignment.lock().unwrap() = assigned;
+    }
+
+    pub fn record_poll(&self) {
+        *self.last_poll_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn last_poll_age(&self) -> Option<Duration> {
+        self.last_poll_at.lock().unwrap().map(|t| t.elapsed())
+    }
+
+    /// Ready only when a partition is currently assigned and the last
+    /// successful poll happened within `max_poll_age`, so orchestrators
+    /// don't route query traffic to a pod that's mid-rebalance or stalled.
+    pub fn is_ready(&self, max_poll_age: Duration) -> bool {
+        *self.has_assignment.lock().unwrap() && self.last_poll_age().is_some_and(|age| age <= max_poll_age)
+    }
+}
+
+impl Default for ConsumerReadiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct HealthChecker {
     discord_healthy: Arc<Mutex<bool>>,
     kafka_healthy: Arc<Mutex<bool>>,
     elasticsearch_healthy: Arc<Mutex<bool>>,
     pinecone_healthy: Arc<Mutex<bool>>,
+    dlq_healthy: Arc<Mutex<bool>>,
+    consumer_readiness: ConsumerReadiness,
 }
 
 impl HealthChecker {
@@ -29,6 +85,25 @@ impl HealthChecker {
             kafka_healthy: Arc::new(Mutex::new(false)),
             elasticsearch_healthy: Arc::new(Mutex::new(false)),
             pinecone_healthy: Arc::new(Mutex::new(false)),
+            dlq_healthy: Arc::new(Mutex::new(false)),
+            consumer_readiness: ConsumerReadiness::new(),
+        }
+    }
+
+    /// Hands out the shared readiness handle so `KafkaConsumer` can report
+    /// its rebalance/poll state back into this `HealthChecker`.
+    pub fn consumer_readiness(&self) -> ConsumerReadiness {
+        self.consumer_readiness.clone()
+    }
+
+    /// Readiness (as opposed to `get_overall_health`'s liveness check): only
+    /// healthy when the consumer holds a partition assignment and polled
+    /// successfully within `max_poll_age`.
+    pub fn get_readiness(&self, max_poll_age: Duration) -> ReadinessStatus {
+        ReadinessStatus {
+            ready: self.consumer_readiness.is_ready(max_poll_age),
+            has_partition_assignment: *self.consumer_readiness.has_assignment.lock().unwrap(),
+            last_poll_age_secs: self.consumer_readiness.last_poll_age().map(|age| age.as_secs_f64()),
         }
     }
 
@@ -48,13 +123,28 @@ impl HealthChecker {
         }
     }
 
-    pub async fn check_kafka(&self) -> ServiceHealth {
+    pub async fn check_kafka(&self, cfg: &crate::config::Config) -> ServiceHealth {
         let start = std::time::Instant::now();
-        
-        // Simple Kafka health check - try to create a producer
+
+        // The in-memory broker backend has no broker connection to probe;
+        // it's healthy by construction.
+        if crate::broker::BrokerBackend::from_env_str(&cfg.kafka_broker_backend) == crate::broker::BrokerBackend::InMemory {
+            *self.kafka_healthy.lock().await = true;
+            return ServiceHealth {
+                status: "healthy".to_string(),
+                message: Some("Using in-memory broker backend".to_string()),
+                response_time_ms: Some(start.elapsed().as_millis() as u64),
+            };
+        }
+
+        // Simple Kafka health check - try to create a producer against the
+        // configured brokers (and security settings), not an empty config.
         use rdkafka::config::FromClientConfig;
         use rdkafka::client::DefaultClientContext;
-        match rdkafka::producer::FutureProducer::<DefaultClientContext, rdkafka::util::TokioRuntime>::from_config(&rdkafka::ClientConfig::new()) {
+        let mut client_config = rdkafka::ClientConfig::new();
+        client_config.set("bootstrap.servers", &cfg.kafka_brokers);
+        cfg.apply_kafka_security(&mut client_config);
+        match rdkafka::producer::FutureProducer::<DefaultClientContext, rdkafka::util::TokioRuntime>::from_config(&client_config) {
             Ok(_) => {
                 let response_time = start.elapsed().as_millis() as u64;
                 *self.kafka_healthy.lock().await = true;
@@ -108,6 +198,32 @@ impl HealthChecker {
         }
     }
 
+    pub async fn check_dlq(&self, cfg: &crate::config::Config) -> ServiceHealth {
+        let start = std::time::Instant::now();
+
+        // Simple DLQ health check - try to create the same kind of producer
+        // `DlqProducer::new` builds, without actually publishing anything.
+        match crate::dlq::DlqProducer::new(cfg) {
+            Ok(_) => {
+                let response_time = start.elapsed().as_millis() as u64;
+                *self.dlq_healthy.lock().await = true;
+                ServiceHealth {
+                    status: "healthy".to_string(),
+                    message: Some("DLQ producer created successfully".to_string()),
+                    response_time_ms: Some(response_time),
+                }
+            }
+            Err(e) => {
+                *self.dlq_healthy.lock().await = false;
+                ServiceHealth {
+                    status: "unhealthy".to_string(),
+                    message: Some(format!("DLQ producer error: {}", e)),
+                    response_time_ms: Some(start.elapsed().as_millis() as u64),
+                }
+            }
+        }
+    }
+
     pub async fn check_pinecone(&self, pinecone_host: &str) -> ServiceHealth {
         let start = std::time::Instant::now();
         
@@ -142,14 +258,15 @@ impl HealthChecker {
         }
     }
 
-    pub async fn get_overall_health(&self, es_url: &str, pinecone_host: &str) -> HealthStatus {
+    pub async fn get_overall_health(&self, cfg: &crate::config::Config) -> HealthStatus {
         let mut services = std::collections::HashMap::new();
-        
+
         // Check all services
         services.insert("discord".to_string(), self.check_discord().await);
-        services.insert("kafka".to_string(), self.check_kafka().await);
-        services.insert("elasticsearch".to_string(), self.check_elasticsearch(es_url).await);
-        services.insert("pinecone".to_string(), self.check_pinecone(pinecone_host).await);
+        services.insert("kafka".to_string(), self.check_kafka(cfg).await);
+        services.insert("elasticsearch".to_string(), self.check_elasticsearch(&cfg.elasticsearch_url).await);
+        services.insert("pinecone".to_string(), self.check_pinecone(&cfg.pinecone_host).await);
+        services.insert("dlq".to_string(), self.check_dlq(cfg).await);
         
         // Determine overall status
         let all_healthy = services.values().all(|service| service.status == "healthy");
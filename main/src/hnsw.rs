@@ -0,0 +1,307 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::cmp::Ordering;
+use std::path::Path;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Metadata stored alongside a vector so the same `guild_id`/`$eq`/`$exists`
+/// filtering semantics used against Pinecone still apply post-search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorMetadata {
+    pub guild_id: Option<String>,
+    pub author_id: Option<String>,
+    pub timestamp: String,
+    pub text: String,
+    pub is_chunk: bool,
+    pub chunk_fields: Option<ChunkFields>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkFields {
+    pub chunk_id: String,
+    pub summary: Option<String>,
+    pub authors: Vec<String>,
+    pub message_count: usize,
+    pub first_timestamp: String,
+    pub last_timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    vector: Vec<f32>,
+    neighbors: Vec<Vec<usize>>, // neighbors[layer] = neighbor ids at that layer
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    id: usize,
+    distance: f32,
+}
+impl Eq for Candidate {}
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.partial_cmp(&self.distance).unwrap_or(Ordering::Equal)
+    }
+}
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `Candidate` orders with the *nearest* distance on top (so a `BinaryHeap`
+/// of it is a min-distance frontier to expand outward from). The `ef`-bounded
+/// result set in `search_layer` needs the opposite: the *farthest* kept
+/// candidate on top, so it's the one evicted once the set grows past `ef`.
+/// This wrapper flips the comparison for exactly that heap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FarCandidate(Candidate);
+impl Eq for FarCandidate {}
+impl Ord for FarCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.distance.partial_cmp(&other.0.distance).unwrap_or(Ordering::Equal)
+    }
+}
+impl PartialOrd for FarCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A small embedded HNSW (Hierarchical Navigable Small World) vector
+/// index, used as a zero-external-dependency alternative to Pinecone.
+/// Each inserted vector is assigned a random top layer from an
+/// exponentially-decaying distribution, linked to its `m` nearest
+/// neighbors found via greedy search down each layer with a degree-bounded
+/// pruning heuristic; queries descend the upper layers greedily then run
+/// an `ef_search`-bounded beam search on layer 0.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HnswIndex {
+    m: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    level_mult: f64,
+    nodes: Vec<Node>,
+    ids: Vec<String>,
+    id_to_index: HashMap<String, usize>,
+    metadata: HashMap<String, VectorMetadata>,
+    entry_point: Option<usize>,
+    max_layer: usize,
+}
+
+impl HnswIndex {
+    pub fn new(m: usize, ef_construction: usize, ef_search: usize) -> Self {
+        Self {
+            m,
+            ef_construction,
+            ef_search,
+            level_mult: 1.0 / (m as f64).ln(),
+            nodes: Vec::new(),
+            ids: Vec::new(),
+            id_to_index: HashMap::new(),
+            metadata: HashMap::new(),
+            entry_point: None,
+            max_layer: 0,
+        }
+    }
+
+    fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+        let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm_a == 0.0 || norm_b == 0.0 {
+            return 1.0;
+        }
+        1.0 - (dot / (norm_a * norm_b))
+    }
+
+    fn random_level(&self) -> usize {
+        let mut rng = rand::thread_rng();
+        let r: f64 = rng.gen_range(0.0..1.0);
+        (-r.ln() * self.level_mult).floor() as usize
+    }
+
+    fn greedy_search_layer(&self, query: &[f32], entry: usize, layer: usize) -> usize {
+        let mut current = entry;
+        let mut current_dist = Self::cosine_distance(query, &self.nodes[current].vector);
+        loop {
+            let mut improved = false;
+            if let Some(neighbors) = self.nodes[current].neighbors.get(layer) {
+                for &neighbor in neighbors {
+                    let dist = Self::cosine_distance(query, &self.nodes[neighbor].vector);
+                    if dist < current_dist {
+                        current = neighbor;
+                        current_dist = dist;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        current
+    }
+
+    /// Beam search bounded by `ef`, returning up to `ef` nearest candidates
+    /// on the given layer starting from `entry`.
+    fn search_layer(&self, query: &[f32], entry: usize, layer: usize, ef: usize) -> Vec<Candidate> {
+        let mut visited = HashSet::new();
+        visited.insert(entry);
+
+        let entry_dist = Self::cosine_distance(query, &self.nodes[entry].vector);
+        let mut candidates = BinaryHeap::new();
+        candidates.push(Candidate { id: entry, distance: entry_dist });
+
+        let mut results = BinaryHeap::new();
+        results.push(FarCandidate(Candidate { id: entry, distance: entry_dist }));
+
+        while let Some(Candidate { id, distance }) = candidates.pop() {
+            let furthest = results.peek().map(|c| c.0.distance).unwrap_or(f32::MAX);
+            if distance > furthest && results.len() >= ef {
+                break;
+            }
+
+            if let Some(neighbors) = self.nodes[id].neighbors.get(layer) {
+                for &neighbor in neighbors {
+                    if !visited.insert(neighbor) {
+                        continue;
+                    }
+                    let dist = Self::cosine_distance(query, &self.nodes[neighbor].vector);
+                    if results.len() < ef || dist < results.peek().map(|c| c.0.distance).unwrap_or(f32::MAX) {
+                        candidates.push(Candidate { id: neighbor, distance: dist });
+                        results.push(FarCandidate(Candidate { id: neighbor, distance: dist }));
+                        if results.len() > ef {
+                            results.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec().into_iter().map(|c| c.0).collect()
+    }
+
+    /// Bounds out-degree by keeping the `m` closest of the candidate
+    /// neighbors, which is the standard HNSW neighbor-pruning heuristic.
+    fn select_neighbors(&self, candidates: Vec<Candidate>, m: usize) -> Vec<usize> {
+        let mut sorted = candidates;
+        sorted.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(Ordering::Equal));
+        sorted.truncate(m);
+        sorted.into_iter().map(|c| c.id).collect()
+    }
+
+    pub fn insert(&mut self, id: String, vector: Vec<f32>, metadata: VectorMetadata) {
+        let level = self.random_level();
+        let new_id = self.nodes.len();
+
+        self.nodes.push(Node {
+            vector: vector.clone(),
+            neighbors: vec![Vec::new(); level + 1],
+        });
+        self.ids.push(id.clone());
+        self.id_to_index.insert(id.clone(), new_id);
+        self.metadata.insert(id, metadata);
+
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(new_id);
+            self.max_layer = level;
+            return;
+        };
+
+        let mut current = entry_point;
+        for layer in (level + 1..=self.max_layer).rev() {
+            current = self.greedy_search_layer(&vector, current, layer);
+        }
+
+        for layer in (0..=level.min(self.max_layer)).rev() {
+            let candidates = self.search_layer(&vector, current, layer, self.ef_construction);
+            let neighbors = self.select_neighbors(candidates, self.m);
+
+            for &neighbor in &neighbors {
+                self.nodes[new_id].neighbors[layer].push(neighbor);
+                if layer < self.nodes[neighbor].neighbors.len() {
+                    self.nodes[neighbor].neighbors[layer].push(new_id);
+                    if self.nodes[neighbor].neighbors[layer].len() > self.m * 2 {
+                        let pruned: Vec<Candidate> = self.nodes[neighbor].neighbors[layer]
+                            .iter()
+                            .map(|&n| Candidate {
+                                id: n,
+                                distance: Self::cosine_distance(&self.nodes[neighbor].vector, &self.nodes[n].vector),
+                            })
+                            .collect();
+                        self.nodes[neighbor].neighbors[layer] = self.select_neighbors(pruned, self.m);
+                    }
+                }
+            }
+            if let Some(&first) = neighbors.first() {
+                current = first;
+            }
+        }
+
+        if level > self.max_layer {
+            self.max_layer = level;
+            self.entry_point = Some(new_id);
+        }
+    }
+
+    /// Greedy descent through the upper layers, then an `ef_search`-bounded
+    /// beam search on layer 0, returning the top-k by cosine distance.
+    /// `filter` lets callers apply the `guild_id` `$eq`/`$exists` semantics
+    /// against the side metadata map.
+    pub fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: impl Fn(&VectorMetadata) -> bool,
+    ) -> Vec<(String, f32, VectorMetadata)> {
+        let Some(entry_point) = self.entry_point else {
+            return vec![];
+        };
+
+        let mut current = entry_point;
+        for layer in (1..=self.max_layer).rev() {
+            current = self.greedy_search_layer(query, current, layer);
+        }
+
+        let ef = self.ef_search.max(top_k);
+        let candidates = self.search_layer(query, current, 0, ef);
+
+        let mut results = Vec::new();
+        for candidate in candidates {
+            let id = &self.ids[candidate.id];
+            if let Some(metadata) = self.metadata.get(id) {
+                if filter(metadata) {
+                    results.push((id.clone(), candidate.distance, metadata.clone()));
+                }
+            }
+        }
+        results.truncate(top_k);
+        results
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DynErr> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer(file, self)?;
+        info!(path = %path.display(), vectors = self.len(), "Persisted HNSW index to disk");
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, DynErr> {
+        let file = std::fs::File::open(path)?;
+        let index: Self = serde_json::from_reader(file)?;
+        info!(path = %path.display(), vectors = index.len(), "Loaded HNSW index from disk");
+        Ok(index)
+    }
+}
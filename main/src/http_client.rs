@@ -0,0 +1,185 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+use rand::Rng;
+use reqwest::Response;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use prometheus::Histogram;
+use crate::metrics::{HTTP_RETRIES_TOTAL, CIRCUIT_BREAKER_STATE};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+const CLOSED: u8 = 0;
+const OPEN: u8 = 1;
+const HALF_OPEN: u8 = 2;
+
+/// Shared `reqwest::Client` reused across every outbound call so TCP
+/// connections and TLS sessions get pooled instead of being thrown away
+/// after a single request.
+pub fn shared_client() -> &'static reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Backoff/retry tuning for [`with_retry`].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Per-backend circuit breaker: trips open after `failure_threshold`
+/// consecutive failures, short-circuits calls while open, and half-opens
+/// after `cooldown`. While half-open every call is let through; the next
+/// recorded success closes the breaker and the next failure re-opens it.
+pub struct CircuitBreaker {
+    name: &'static str,
+    state: AtomicU8,
+    consecutive_failures: AtomicU32,
+    failure_threshold: u32,
+    cooldown: Duration,
+    opened_at: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            state: AtomicU8::new(CLOSED),
+            consecutive_failures: AtomicU32::new(0),
+            failure_threshold,
+            cooldown,
+            opened_at: Mutex::new(None),
+        }
+    }
+
+    /// Returns true if the breaker should currently short-circuit calls.
+    /// Transitions Open -> HalfOpen once the cooldown elapses; calls are
+    /// not short-circuited while half-open.
+    async fn should_short_circuit(&self) -> bool {
+        if self.state.load(Ordering::SeqCst) != OPEN {
+            return false;
+        }
+
+        let opened_at = *self.opened_at.lock().await;
+        match opened_at {
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => {
+                self.state.store(HALF_OPEN, Ordering::SeqCst);
+                CIRCUIT_BREAKER_STATE.with_label_values(&[self.name]).set(HALF_OPEN as f64);
+                info!(backend = self.name, "Circuit breaker half-open, probing recovery");
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    async fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+        if self.state.swap(CLOSED, Ordering::SeqCst) != CLOSED {
+            info!(backend = self.name, "Circuit breaker closed after successful probe");
+            CIRCUIT_BREAKER_STATE.with_label_values(&[self.name]).set(CLOSED as f64);
+        }
+    }
+
+    async fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures >= self.failure_threshold && self.state.load(Ordering::SeqCst) != OPEN {
+            self.state.store(OPEN, Ordering::SeqCst);
+            *self.opened_at.lock().await = Some(Instant::now());
+            warn!(backend = self.name, failures, "Circuit breaker tripped open");
+            CIRCUIT_BREAKER_STATE.with_label_values(&[self.name]).set(OPEN as f64);
+        }
+    }
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.as_u16() == 429 || matches!(status.as_u16(), 502 | 503 | 504)
+}
+
+fn backoff_delay(attempt: u32, cfg: &RetryConfig, retry_after: Option<Duration>) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(cfg.max_delay);
+    }
+
+    let exp = cfg.base_delay.as_millis() as u64 * 2u64.saturating_pow(attempt);
+    let capped = exp.min(cfg.max_delay.as_millis() as u64);
+    let jitter_factor = rand::thread_rng().gen_range(0.5..1.5);
+    Duration::from_millis((capped as f64 * jitter_factor) as u64)
+}
+
+/// Sends a request built fresh by `make_request` on each attempt (so the
+/// body doesn't need to be re-cloned by the caller), retrying transient
+/// connection errors and 429/502/503/504 responses with exponential
+/// backoff plus jitter, honoring `Retry-After` when present. Starts/stops
+/// `histogram` around the whole call and consults/updates `breaker` so a
+/// tripped backend fails fast instead of piling up retries.
+pub async fn with_retry<F, Fut>(
+    breaker: &CircuitBreaker,
+    histogram: &Histogram,
+    cfg: &RetryConfig,
+    mut make_request: F,
+) -> Result<Response, DynErr>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response, reqwest::Error>>,
+{
+    if breaker.should_short_circuit().await {
+        return Err(format!("Circuit breaker open for {}", breaker.name).into());
+    }
+
+    let _timer = histogram.start_timer();
+    let mut attempt = 0;
+
+    loop {
+        match make_request().await {
+            Ok(response) if response.status().is_success() => {
+                breaker.record_success().await;
+                return Ok(response);
+            }
+            Ok(response) if is_retryable_status(response.status()) && attempt < cfg.max_retries => {
+                let retry_after = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                attempt += 1;
+                HTTP_RETRIES_TOTAL.with_label_values(&[breaker.name]).inc();
+                tokio::time::sleep(backoff_delay(attempt, cfg, retry_after)).await;
+            }
+            Ok(response) => {
+                breaker.record_failure().await;
+                return Ok(response);
+            }
+            Err(err) if attempt < cfg.max_retries => {
+                attempt += 1;
+                warn!(service = breaker.name, attempt, error = %err, "Request failed, retrying");
+                HTTP_RETRIES_TOTAL.with_label_values(&[breaker.name]).inc();
+                tokio::time::sleep(backoff_delay(attempt, cfg, None)).await;
+            }
+            Err(err) => {
+                breaker.record_failure().await;
+                return Err(err.into());
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref PINECONE_BREAKER: CircuitBreaker = CircuitBreaker::new("pinecone", 5, Duration::from_secs(30));
+    pub static ref ELASTICSEARCH_BREAKER: CircuitBreaker = CircuitBreaker::new("elasticsearch", 5, Duration::from_secs(30));
+    pub static ref COHERE_BREAKER: CircuitBreaker = CircuitBreaker::new("cohere", 5, Duration::from_secs(30));
+}
@@ -0,0 +1,108 @@
+use futures::stream::{self, StreamExt};
+use tracing::{info, warn};
+use crate::{
+    config::Config,
+    schema::MessageEvent,
+    elasticsearch::ElasticsearchClient,
+    pinecone::upsert_batch_to_pinecone,
+    metrics::{MESSAGES_PROCESSED, MESSAGES_FAILED},
+};
+
+/// Pinecone's `/vectors/upsert` endpoint limits requests to roughly
+/// 2MB / 100 vectors; each chunk is capped at the 100-vector count (the
+/// byte size of a chunk is not checked).
+const MAX_PINECONE_CHUNK: usize = 100;
+const MIN_CHUNK: usize = 1;
+
+/// Picks a per-request chunk size from the input size and worker count
+/// rather than a fixed constant: `clamp(ceil(total / num_workers), min, max)`.
+/// `max` is additionally capped by the caller to respect a backend's
+/// request-size limit.
+pub fn compute_chunk_size(total: usize, num_workers: usize, max: usize) -> usize {
+    if total == 0 || num_workers == 0 {
+        return MIN_CHUNK;
+    }
+    let ideal = (total + num_workers - 1) / num_workers;
+    ideal.clamp(MIN_CHUNK, max)
+}
+
+/// Upserts `items` to Pinecone in adaptively-sized batches, dispatched
+/// across `num_workers` concurrent tasks. A partial `_bulk`-style failure
+/// in one chunk doesn't fail the others; per-item success/failure is
+/// reflected in `MESSAGES_PROCESSED`/`MESSAGES_FAILED`.
+pub async fn upsert_batch_concurrent(
+    cfg: &Config,
+    items: Vec<(MessageEvent, Vec<f32>)>,
+    num_workers: usize,
+) {
+    let total = items.len();
+    if total == 0 {
+        return;
+    }
+
+    let chunk_size = compute_chunk_size(total, num_workers, MAX_PINECONE_CHUNK);
+    let chunks: Vec<_> = items.chunks(chunk_size).map(|c| c.to_vec()).collect();
+
+    info!(total, chunk_size, chunks = chunks.len(), "Dispatching adaptive Pinecone batch upsert");
+
+    let tasks = chunks.into_iter().map(|chunk| async move {
+        match upsert_batch_to_pinecone(cfg, &chunk).await {
+            Ok(failed_ids) => {
+                let failed = failed_ids.len();
+                let succeeded = chunk.len() - failed;
+                MESSAGES_PROCESSED.inc_by(succeeded as f64);
+                MESSAGES_FAILED.inc_by(failed as f64);
+                if failed > 0 {
+                    warn!(failed_ids = ?failed_ids, "Some items in Pinecone batch failed");
+                }
+            }
+            Err(err) => {
+                warn!(error = %err, count = chunk.len(), "Pinecone batch upsert chunk failed entirely");
+                MESSAGES_FAILED.inc_by(chunk.len() as f64);
+            }
+        }
+    });
+
+    stream::iter(tasks).buffer_unordered(num_workers.max(1)).collect::<Vec<()>>().await;
+}
+
+/// Indexes `messages` into ElasticSearch in adaptively-sized `_bulk`
+/// batches, dispatched across `num_workers` concurrent tasks. `max_bytes`
+/// caps each batch's estimated NDJSON payload size.
+pub async fn bulk_index_concurrent(
+    es_client: &ElasticsearchClient,
+    messages: Vec<MessageEvent>,
+    num_workers: usize,
+    max_bytes: usize,
+) {
+    let total = messages.len();
+    if total == 0 {
+        return;
+    }
+
+    // A rough per-message NDJSON size estimate keeps each chunk under the
+    // configured byte ceiling without having to serialize up front.
+    let avg_message_bytes = messages.iter().map(|m| m.text.len() + 256).sum::<usize>() / total.max(1);
+    let max_by_bytes = (max_bytes / avg_message_bytes.max(1)).max(MIN_CHUNK);
+    let chunk_size = compute_chunk_size(total, num_workers, max_by_bytes);
+    let chunks: Vec<_> = messages.chunks(chunk_size).map(|c| c.to_vec()).collect();
+
+    info!(total, chunk_size, chunks = chunks.len(), "Dispatching adaptive ElasticSearch bulk index");
+
+    let tasks = chunks.into_iter().map(|chunk| async move {
+        match es_client.bulk_index(&chunk).await {
+            Ok(failed_ids) => {
+                let failed = failed_ids.len();
+                let succeeded = chunk.len() - failed;
+                MESSAGES_PROCESSED.inc_by(succeeded as f64);
+                MESSAGES_FAILED.inc_by(failed as f64);
+            }
+            Err(err) => {
+                warn!(error = %err, count = chunk.len(), "ElasticSearch bulk index chunk failed entirely");
+                MESSAGES_FAILED.inc_by(chunk.len() as f64);
+            }
+        }
+    });
+
+    stream::iter(tasks).buffer_unordered(num_workers.max(1)).collect::<Vec<()>>().await;
+}
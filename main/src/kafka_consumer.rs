@@ -1,45 +1,311 @@
-use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::{ClientConfig, Message};
+use async_trait::async_trait;
+use rdkafka::consumer::{BaseConsumer, CommitMode, Consumer, ConsumerContext, Rebalance, StreamConsumer};
+use rdkafka::producer::FutureProducer;
+use rdkafka::topic_partition_list::{Offset, TopicPartitionList};
+use rdkafka::{ClientConfig, ClientContext, Message};
+use jsonschema::{Draft, JSONSchema};
 use serde_json;
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 use crate::{
+    broker::{ConsumedMessage, MessageConsumer, MessageProducer},
     config::Config,
-    kafka_types::KafkaMessage,
-    cohere::{get_embedding, generate_response, generate_response_from_chunks},
+    kafka_types::{KafkaEventType, KafkaMessage, QuerySource},
+    cohere::{get_embedding, generate_response, generate_response_from_chunks, EmbeddingInputType},
+    kafka_producer::KafkaMessageProducer,
     pinecone::{upsert_to_pinecone, query_pinecone, query_chunks_pinecone},
     chunking::ChunkManager,
-    metrics::{KAFKA_MESSAGES_RECEIVED, MESSAGES_PROCESSED, MESSAGES_FAILED},
+    dlq::{DlqProducer, FailureKind},
+    health::ConsumerReadiness,
+    metrics::{KAFKA_MESSAGES_RECEIVED, MESSAGES_PROCESSED, MESSAGES_FAILED, MESSAGES_INVALID, QUERY_RESPONSE_LATENCY},
+    telemetry,
 };
 
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+type PendingOffsets = Arc<StdMutex<HashMap<(String, i32), i64>>>;
+
+/// Custom `ConsumerContext` that keeps `HealthChecker`'s readiness flag
+/// in sync with this consumer's partition assignment, and commits whatever
+/// offsets are still pending for a partition before it's revoked so a
+/// rebalance never re-delivers more than the in-flight batch.
+pub struct ReadinessContext {
+    readiness: ConsumerReadiness,
+    pending_offsets: PendingOffsets,
+}
+
+impl ReadinessContext {
+    fn new(readiness: ConsumerReadiness, pending_offsets: PendingOffsets) -> Self {
+        Self { readiness, pending_offsets }
+    }
+}
+
+impl ClientContext for ReadinessContext {}
+
+impl ConsumerContext for ReadinessContext {
+    fn pre_rebalance(&self, base_consumer: &BaseConsumer<Self>, rebalance: &Rebalance) {
+        if let Rebalance::Revoke(revoked) = rebalance {
+            warn!(partitions = ?revoked, "Kafka rebalance: revoking partitions, committing outstanding offsets first");
+            self.readiness.set_assigned(false);
+
+            let mut pending = self.pending_offsets.lock().unwrap();
+            let mut tpl = TopicPartitionList::new();
+            for elem in revoked.elements() {
+                if let Some(offset) = pending.remove(&(elem.topic().to_string(), elem.partition())) {
+                    if let Err(err) = tpl.add_partition_offset(elem.topic(), elem.partition(), Offset::Offset(offset + 1)) {
+                        error!(topic = elem.topic(), partition = elem.partition(), error = %err, "Failed to stage offset for pre-revoke commit");
+                    }
+                }
+            }
+
+            if tpl.count() > 0 {
+                if let Err(err) = base_consumer.commit(&tpl, CommitMode::Sync) {
+                    error!(error = %err, "Failed to commit offsets before partition revoke");
+                }
+            }
+        }
+    }
+
+    fn post_rebalance(&self, _base_consumer: &BaseConsumer<Self>, rebalance: &Rebalance) {
+        match rebalance {
+            Rebalance::Assign(assigned) => {
+                info!(partitions = ?assigned, "Kafka rebalance: partitions assigned");
+                self.readiness.set_assigned(assigned.count() > 0);
+            }
+            Rebalance::Revoke(_) => {
+                self.readiness.set_assigned(false);
+            }
+            Rebalance::Error(err) => {
+                error!(error = %err, "Kafka rebalance error");
+                self.readiness.set_assigned(false);
+            }
+        }
+    }
+}
+
+/// [`MessageConsumer`] wrapper around `StreamConsumer<ReadinessContext>`, so
+/// `KafkaConsumer` depends on the trait rather than concretely on `rdkafka`.
+pub struct KafkaMessageConsumer {
+    consumer: StreamConsumer<ReadinessContext>,
+}
+
+impl KafkaMessageConsumer {
+    fn new(consumer: StreamConsumer<ReadinessContext>) -> Self {
+        Self { consumer }
+    }
+}
+
+#[async_trait]
+impl MessageConsumer for KafkaMessageConsumer {
+    async fn subscribe(&self, topics: &[&str]) -> Result<(), DynErr> {
+        self.consumer.subscribe(topics)?;
+        Ok(())
+    }
+
+    async fn recv(&self) -> Result<ConsumedMessage, DynErr> {
+        let message = self.consumer.recv().await?;
+        let payload = message.payload().ok_or("Kafka message has no payload")?.to_vec();
+        let trace_context = telemetry::extract_trace_context(message.headers());
+
+        Ok(ConsumedMessage {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            payload,
+            trace_context,
+        })
+    }
+
+    async fn commit(&self, commits: &[((String, i32), i64)]) -> Result<(), DynErr> {
+        let mut tpl = TopicPartitionList::new();
+        for ((topic, partition), offset) in commits {
+            tpl.add_partition_offset(topic, *partition, Offset::Offset(offset + 1))?;
+        }
+        self.consumer.commit(&tpl, CommitMode::Async)?;
+        Ok(())
+    }
+}
+
+/// Maps an event type to the schema file that validates its `KafkaMessage`
+/// envelope. Event types with no entry here (or whose file fails to load)
+/// are left unvalidated, matching the rest of this module's
+/// "not all variants are wired up yet" convention.
+fn schema_filename(event_type: KafkaEventType) -> Option<&'static str> {
+    match event_type {
+        KafkaEventType::DiscordMessage => Some("discord_message.schema.json"),
+        KafkaEventType::QueryRequest => Some("query_request.schema.json"),
+        _ => None,
+    }
+}
+
+/// Distinguishes processing failures that can never succeed on retry (bad
+/// JSON, a missing payload field) from transient ones (Cohere/Pinecone
+/// network errors), so `process_with_retry` knows whether to retry before
+/// giving up to the DLQ.
+#[derive(Debug)]
+enum ConsumeError {
+    Invalid(String),
+    Transient(String),
+}
+
+impl std::fmt::Display for ConsumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsumeError::Invalid(msg) => write!(f, "invalid message: {msg}"),
+            ConsumeError::Transient(msg) => write!(f, "transient failure: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ConsumeError {}
+
 pub struct KafkaConsumer {
-    consumer: StreamConsumer,
+    consumer: Box<dyn MessageConsumer>,
     cfg: Config,
     chunk_manager: ChunkManager,
+    dlq_producer: Option<DlqProducer>,
+    response_producer: Box<dyn MessageProducer>,
+    readiness: ConsumerReadiness,
+    schemas: HashMap<KafkaEventType, JSONSchema>,
+    /// Highest offset safely processed (or DLQ'd) per partition, not yet
+    /// committed to the broker. Committing `offset + 1` (Kafka's convention
+    /// for "next offset to read") is deferred until a batch boundary so we
+    /// avoid a per-message round trip while still only advancing past
+    /// messages we've actually finished with. Shared with `ReadinessContext`
+    /// so a pre-rebalance revoke can commit (and clear) a partition's
+    /// pending offset before it's taken away.
+    pending_offsets: PendingOffsets,
+    last_commit: Instant,
+    /// Messages processed (or DLQ'd) since the last commit. `pending_offsets`
+    /// only ever holds one entry per partition, so its length can't stand in
+    /// for a message count - this is what `kafka_commit_batch_size` is
+    /// actually measured against.
+    messages_since_commit: usize,
 }
 
 impl KafkaConsumer {
-    pub fn new(cfg: Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let consumer: StreamConsumer = ClientConfig::new()
+    pub fn new(cfg: Config, readiness: ConsumerReadiness) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let pending_offsets: PendingOffsets = Arc::new(StdMutex::new(HashMap::new()));
+        let context = ReadinessContext::new(readiness.clone(), pending_offsets.clone());
+
+        let mut consumer_config = ClientConfig::new();
+        consumer_config
             .set("group.id", &cfg.kafka_group_id)
             .set("bootstrap.servers", &cfg.kafka_brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "30000")
-            .set("enable.auto.commit", "true")
-            .set("auto.commit.interval.ms", "5000")
+            .set("enable.auto.commit", "false")
             .set("auto.offset.reset", "earliest")
             .set("max.poll.interval.ms", "600000") // 10 minutes
-            .set("heartbeat.interval.ms", "10000") // 10 seconds
-            .create()?;
+            .set("heartbeat.interval.ms", "10000"); // 10 seconds
+        cfg.apply_kafka_security(&mut consumer_config);
+        let stream_consumer: StreamConsumer<ReadinessContext> =
+            consumer_config.create_with_context(context)?;
+        let consumer: Box<dyn MessageConsumer> = Box::new(KafkaMessageConsumer::new(stream_consumer));
+
+        let dlq_producer = match DlqProducer::new(&cfg) {
+            Ok(producer) => Some(producer),
+            Err(err) => {
+                warn!("Failed to initialize DLQ producer: {}. Un-processable messages will only be logged.", err);
+                None
+            }
+        };
+
+        let schemas = Self::load_schemas(&cfg);
+
+        let mut response_producer_config = ClientConfig::new();
+        response_producer_config
+            .set("bootstrap.servers", &cfg.kafka_brokers)
+            .set("message.timeout.ms", "5000");
+        cfg.apply_kafka_security(&mut response_producer_config);
+        let future_producer: FutureProducer = response_producer_config.create()?;
+        let response_producer: Box<dyn MessageProducer> = Box::new(KafkaMessageProducer::new(future_producer));
 
         Ok(Self {
             consumer,
             cfg,
             chunk_manager: ChunkManager::new(),
+            dlq_producer,
+            response_producer,
+            readiness,
+            schemas,
+            pending_offsets,
+            last_commit: Instant::now(),
+            messages_since_commit: 0,
         })
     }
 
+    /// Builds a `KafkaConsumer` around an already-constructed broker pair
+    /// instead of dialing `cfg.kafka_brokers`, so tests can drive the whole
+    /// `process_message` pipeline against an [`crate::broker::InMemoryBroker`]
+    /// without a running Kafka cluster.
+    pub fn with_broker(
+        cfg: Config,
+        readiness: ConsumerReadiness,
+        consumer: Box<dyn MessageConsumer>,
+        response_producer: Box<dyn MessageProducer>,
+    ) -> Self {
+        let schemas = Self::load_schemas(&cfg);
+
+        Self {
+            consumer,
+            cfg,
+            chunk_manager: ChunkManager::new(),
+            dlq_producer: None,
+            response_producer,
+            readiness,
+            schemas,
+            pending_offsets: Arc::new(StdMutex::new(HashMap::new())),
+            last_commit: Instant::now(),
+            messages_since_commit: 0,
+        }
+    }
+
+    /// Compiles the Draft-7 schemas declared in `schema_filename` from
+    /// `cfg.kafka_schema_dir`. Missing or invalid schema files only disable
+    /// validation for that event type, they never fail startup, matching
+    /// how `dlq_producer` above degrades gracefully.
+    fn load_schemas(cfg: &Config) -> HashMap<KafkaEventType, JSONSchema> {
+        let event_types = [KafkaEventType::DiscordMessage, KafkaEventType::QueryRequest];
+        let mut schemas = HashMap::new();
+
+        for event_type in event_types {
+            let Some(filename) = schema_filename(event_type) else {
+                continue;
+            };
+            let path = std::path::Path::new(&cfg.kafka_schema_dir).join(filename);
+
+            let schema_value = match std::fs::read_to_string(&path) {
+                Ok(contents) => match serde_json::from_str(&contents) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        warn!(path = %path.display(), error = %err, "Failed to parse JSON schema, skipping validation for this event type");
+                        continue;
+                    }
+                },
+                Err(err) => {
+                    warn!(path = %path.display(), error = %err, "Failed to read JSON schema, skipping validation for this event type");
+                    continue;
+                }
+            };
+
+            match JSONSchema::options().with_draft(Draft::Draft7).compile(&schema_value) {
+                Ok(compiled) => {
+                    schemas.insert(event_type, compiled);
+                }
+                Err(err) => {
+                    warn!(path = %path.display(), error = %err, "Failed to compile JSON schema, skipping validation for this event type");
+                }
+            }
+        }
+
+        schemas
+    }
+
     pub async fn subscribe_to_topics(&self, topics: &[&str]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.consumer.subscribe(topics)?;
+        self.consumer.subscribe(topics).await?;
         info!(topics = ?topics, "Subscribed to Kafka topics");
         Ok(())
     }
@@ -49,42 +315,137 @@ impl KafkaConsumer {
         info!("Starting Kafka consumer...");
 
         loop {
-            let (payload, topic, partition, offset) = {
-                let message = match self.consumer.recv().await {
-                    Ok(msg) => msg,
-                    Err(e) => {
-                        error!(error = %e, "Error receiving message from Kafka");
-                        continue;
-                    }
-                };
-
-                if let Some(payload) = message.payload() {
-                    let topic = message.topic().to_string();
-                    let partition = message.partition();
-                    let offset = message.offset();
-                    (payload.to_vec(), topic, partition, offset)
-                } else {
+            let ConsumedMessage { topic, partition, offset, payload, trace_context } = match self.consumer.recv().await {
+                Ok(msg) => msg,
+                Err(e) => {
+                    error!(error = %e, "Error receiving message from broker");
                     continue;
                 }
             };
-            
+            self.readiness.record_poll();
+
+            // Root span for this message's whole trip through the pipeline;
+            // `trace_context` re-parents it onto whatever trace the producer
+            // started, so a Discord message can be traced end-to-end across
+            // the bot -> Kafka -> consumer -> Pinecone boundary.
+            let span = tracing::info_span!("consume_kafka_message", topic = %topic, partition, offset);
+            span.set_parent(trace_context);
+
             KAFKA_MESSAGES_RECEIVED.inc();
-            match self.process_message(&payload).await {
-                Ok(_) => {
+            self.process_with_retry(&payload, &topic, partition, offset).instrument(span).await;
+
+            // Only offsets for messages we've fully processed (or given up on
+            // and handed to the DLQ) are ever recorded here, so committing
+            // them gives at-least-once delivery even if the consumer crashes
+            // between commits.
+            self.pending_offsets.lock().unwrap().insert((topic, partition), offset);
+            self.messages_since_commit += 1;
+            self.maybe_commit().await;
+        }
+    }
+
+    /// Commits the highest pending offset per partition once a batch of
+    /// `kafka_commit_batch_size` messages has accumulated or
+    /// `kafka_commit_interval_ms` has elapsed since the last commit,
+    /// whichever comes first.
+    async fn maybe_commit(&mut self) {
+        let interval_elapsed = self.last_commit.elapsed() >= Duration::from_millis(self.cfg.kafka_commit_interval_ms);
+
+        let batch_full = self.messages_since_commit >= self.cfg.kafka_commit_batch_size;
+        let should_commit = {
+            let pending = self.pending_offsets.lock().unwrap();
+            !pending.is_empty() && (batch_full || interval_elapsed)
+        };
+        if !should_commit {
+            return;
+        }
+
+        let commits: Vec<((String, i32), i64)> = {
+            let mut pending = self.pending_offsets.lock().unwrap();
+            pending.drain().collect()
+        };
+
+        if let Err(err) = self.consumer.commit(&commits).await {
+            error!(error = %err, "Failed to commit offsets");
+        } else {
+            debug!(partitions = commits.len(), messages = self.messages_since_commit, "Committed offsets");
+        }
+
+        self.last_commit = Instant::now();
+        self.messages_since_commit = 0;
+    }
+
+    /// Processes one message, retrying transient failures up to
+    /// `cfg.kafka_max_retries` times with exponential backoff before giving
+    /// up to the DLQ. Invalid messages skip straight to the DLQ since no
+    /// amount of retrying fixes bad JSON or a missing field. Either way it
+    /// returns normally, so `start_consuming` records the offset for the next
+    /// batched commit and the pipeline doesn't stall on a single
+    /// un-processable message.
+    async fn process_with_retry(&mut self, payload: &[u8], topic: &str, partition: i32, offset: i64) {
+        let mut attempt = 0;
+
+        loop {
+            match self.process_message(payload).await {
+                Ok(()) => {
                     MESSAGES_PROCESSED.inc();
-                    debug!(topic = %topic, partition = partition, offset = offset, "Processed message successfully");
+                    debug!(topic, partition, offset, "Processed message successfully");
+                    return;
                 }
-                Err(err) => {
+                Err(ConsumeError::Invalid(msg)) => {
+                    warn!(topic, partition, offset, error = %msg, "Message is not processable, sending to DLQ");
+                    self.send_to_dlq(payload, FailureKind::Invalid, &msg, topic, partition, offset, attempt).await;
+                    MESSAGES_FAILED.inc();
+                    return;
+                }
+                Err(ConsumeError::Transient(msg)) if attempt < self.cfg.kafka_max_retries => {
+                    attempt += 1;
+                    warn!(topic, partition, offset, attempt, error = %msg, "Transient failure processing message, retrying");
+                    tokio::time::sleep(self.retry_delay(attempt)).await;
+                }
+                Err(ConsumeError::Transient(msg)) => {
+                    error!(topic, partition, offset, attempts = attempt, error = %msg, "Exhausted retries, sending to DLQ");
+                    self.send_to_dlq(payload, FailureKind::Transient, &msg, topic, partition, offset, attempt).await;
                     MESSAGES_FAILED.inc();
-                    error!(error = %err, topic = %topic, "Failed to process message");
+                    return;
                 }
             }
         }
     }
 
-    async fn process_message(&mut self, payload: &[u8]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let kafka_message: KafkaMessage = serde_json::from_slice(payload)?;
-        
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let base = self.cfg.kafka_retry_base_delay_ms;
+        Duration::from_millis(base.saturating_mul(2u64.saturating_pow(attempt - 1)))
+    }
+
+    async fn send_to_dlq(&self, payload: &[u8], kind: FailureKind, error: &str, topic: &str, partition: i32, offset: i64, retry_count: u32) {
+        let Some(ref dlq_producer) = self.dlq_producer else {
+            error!(topic, partition, offset, "No DLQ producer configured; dropping un-processable message");
+            return;
+        };
+
+        if let Err(err) = dlq_producer.send(&self.cfg, payload, kind, error, topic, partition, offset, retry_count).await {
+            error!(error = %err, topic, partition, offset, "Failed to publish message to DLQ; dropping it");
+        }
+    }
+
+    async fn process_message(&mut self, payload: &[u8]) -> Result<(), ConsumeError> {
+        let value: serde_json::Value = serde_json::from_slice(payload)
+            .map_err(|err| ConsumeError::Invalid(format!("payload is not valid JSON: {err}")))?;
+
+        if let Some(event_type) = value.get("event_type").and_then(|v| serde_json::from_value::<KafkaEventType>(v.clone()).ok()) {
+            if let Some(schema) = self.schemas.get(&event_type) {
+                if let Err(errors) = schema.validate(&value) {
+                    let joined = errors.map(|e| e.to_string()).collect::<Vec<_>>().join("; ");
+                    MESSAGES_INVALID.inc();
+                    return Err(ConsumeError::Invalid(format!("schema validation failed: {joined}")));
+                }
+            }
+        }
+
+        let kafka_message: KafkaMessage = serde_json::from_value(value)
+            .map_err(|err| ConsumeError::Invalid(format!("failed to deserialize KafkaMessage: {err}")))?;
+
         match kafka_message.event_type {
             crate::kafka_types::KafkaEventType::DiscordMessage => {
                 self.handle_discord_message(kafka_message).await
@@ -99,57 +460,95 @@ impl KafkaConsumer {
         }
     }
 
-    async fn handle_discord_message(&mut self, message: KafkaMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let crate::kafka_types::KafkaPayload::DiscordMessage(msg_event) = message.payload {
-            info!(message_id = %msg_event.id, "Processing Discord message from Kafka");
+    async fn handle_discord_message(&mut self, message: KafkaMessage) -> Result<(), ConsumeError> {
+        let crate::kafka_types::KafkaPayload::DiscordMessage(msg_event) = message.payload else {
+            return Err(ConsumeError::Invalid("expected a DiscordMessage payload".to_string()));
+        };
 
-            // Process through chunking system
-            if let Err(err) = self.chunk_manager.process_message(&self.cfg, msg_event.clone()).await {
-                error!(error = %err, "Failed to process message through chunking");
-            }
+        info!(message_id = %msg_event.id, "Processing Discord message from Kafka");
 
-            // Also process as individual message for fallback
-            match get_embedding(&self.cfg, &msg_event.text).await {
-                Ok(embedding) => {
-                    if let Err(err) = upsert_to_pinecone(&self.cfg, &msg_event, embedding).await {
-                        error!(error = %err, "Failed to upsert individual message");
-                    }
-                }
-                Err(err) => {
-                    error!(error = %err, "Failed to get embedding for individual message");
-                }
+        // Process through chunking system
+        self.chunk_manager.process_message(&self.cfg, msg_event.clone()).await
+            .map_err(|err| ConsumeError::Transient(format!("chunking failed: {err}")))?;
+
+        // Also process as individual message for fallback
+        let embedding = get_embedding(&self.cfg, &msg_event.text, EmbeddingInputType::SearchDocument).await
+            .map_err(|err| ConsumeError::Transient(format!("embedding failed: {err}")))?;
+
+        upsert_to_pinecone(&self.cfg, &msg_event, embedding).await
+            .map_err(|err| ConsumeError::Transient(format!("pinecone upsert failed: {err}")))?;
+
+        Ok(())
+    }
+
+    async fn handle_query_request(&mut self, message: KafkaMessage) -> Result<(), ConsumeError> {
+        let request_id = message.message_id.clone();
+        let channel_id = message.channel_id.clone();
+        let started_at = Instant::now();
+
+        let crate::kafka_types::KafkaPayload::QueryRequest { question, user_id, guild_id, platform, filters } = message.payload else {
+            return Err(ConsumeError::Invalid("expected a QueryRequest payload".to_string()));
+        };
+
+        info!(question = %question, user_id = %user_id, platform = ?platform, "Processing query request");
+
+        let embedding = get_embedding(&self.cfg, &question, EmbeddingInputType::SearchQuery).await
+            .map_err(|err| ConsumeError::Transient(format!("embedding failed: {err}")))?;
+
+        let similar_chunks = query_chunks_pinecone(&self.cfg, embedding.clone(), 3, guild_id.clone(), platform, &filters).await
+            .map_err(|err| ConsumeError::Transient(format!("pinecone chunk query failed: {err}")))?;
+
+        let (answer, sources) = if !similar_chunks.is_empty() {
+            let answer = generate_response_from_chunks(&self.cfg, &question, &similar_chunks).await
+                .map_err(|err| ConsumeError::Transient(format!("cohere chat failed: {err}")))?;
+            let sources = similar_chunks.iter()
+                .map(|chunk| QuerySource { chunk_id: chunk.chunk_id.clone(), score: chunk.score })
+                .collect();
+            (answer, sources)
+        } else {
+            let similar_messages = query_pinecone(&self.cfg, embedding, 5, guild_id.clone(), platform, &filters).await
+                .map_err(|err| ConsumeError::Transient(format!("pinecone query failed: {err}")))?;
+            if similar_messages.is_empty() {
+                (String::new(), Vec::new())
+            } else {
+                let answer = generate_response(&self.cfg, &question, &similar_messages).await
+                    .map_err(|err| ConsumeError::Transient(format!("cohere chat failed: {err}")))?;
+                (answer, Vec::new())
             }
-        }
+        };
+
+        info!(request_id = %request_id, answer = %answer, "Generated query response");
+
+        let response = KafkaMessage::new_query_response(
+            request_id, channel_id, guild_id, question, answer, user_id, sources,
+        );
+        self.send_query_response(response).await;
+
+        QUERY_RESPONSE_LATENCY.observe(started_at.elapsed().as_secs_f64());
+
         Ok(())
     }
 
-    async fn handle_query_request(&mut self, message: KafkaMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let crate::kafka_types::KafkaPayload::QueryRequest { question, user_id, guild_id } = message.payload {
-            info!(question = %question, user_id = %user_id, "Processing query request");
-
-            // This would typically send the response back to Discord
-            // For now, we'll just log that we processed it
-            match get_embedding(&self.cfg, &question).await {
-                Ok(embedding) => {
-                    // Query Pinecone for similar content
-                    let similar_chunks = query_chunks_pinecone(&self.cfg, embedding.clone(), 3, guild_id.clone()).await?;
-                    
-                    if !similar_chunks.is_empty() {
-                        let response = generate_response_from_chunks(&self.cfg, &question, &similar_chunks).await?;
-                        info!(response = %response, "Generated response from chunks");
-                    } else {
-                        let similar_messages = query_pinecone(&self.cfg, embedding, 5, guild_id).await?;
-                        if !similar_messages.is_empty() {
-                            let response = generate_response(&self.cfg, &question, &similar_messages).await?;
-                            info!(response = %response, "Generated response from messages");
-                        }
-                    }
-                }
-                Err(err) => {
-                    error!(error = %err, "Failed to get embedding for query");
-                }
+    /// Publishes a `QueryResponse` to `cfg.kafka_query_responses_topic` so
+    /// the Discord-facing service can match it back to the interaction via
+    /// `request_id` and deliver the answer. Failing to publish is logged,
+    /// not propagated, the same way `send_to_dlq` treats a DLQ publish
+    /// failure: the inbound offset still advances rather than retrying
+    /// forever.
+    async fn send_query_response(&self, response: KafkaMessage) {
+        let topic = self.cfg.kafka_query_responses_topic.clone();
+        let key = response.get_partition_key();
+
+        let payload = match serde_json::to_vec(&response) {
+            Ok(payload) => payload,
+            Err(err) => {
+                error!(error = %err, topic = %topic, "Failed to serialize QueryResponse");
+                return;
             }
+        };
+
+        if let Err(err) = self.response_producer.send(&topic, &key, payload).await {
+            error!(error = %err, topic = %topic, "Failed to publish QueryResponse");
         }
-        Ok(())
     }
 }
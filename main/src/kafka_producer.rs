@@ -1,41 +1,55 @@
+use async_trait::async_trait;
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::ClientConfig;
 use serde_json;
 use tracing::{info, error};
 use std::time::Duration;
-use crate::{config::Config, kafka_types::{KafkaMessage, DISCORD_MESSAGES_TOPIC}};
+use crate::{broker::MessageProducer, config::Config, kafka_types::{KafkaMessage, DISCORD_MESSAGES_TOPIC}, telemetry};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
 
 pub struct KafkaProducer {
     producer: FutureProducer,
+    partition_count: u32,
 }
 
 impl KafkaProducer {
     pub fn new(cfg: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let producer: FutureProducer = ClientConfig::new()
+        let mut client_config = ClientConfig::new();
+        client_config
             .set("bootstrap.servers", &cfg.kafka_brokers)
             .set("message.timeout.ms", "5000")
             .set("delivery.timeout.ms", "10000")
             .set("request.timeout.ms", "30000")
             .set("retries", "3")
             .set("acks", "all")
-            .set("enable.idempotence", "true")
-            .create()?;
+            .set("enable.idempotence", "true");
+        cfg.apply_kafka_security(&mut client_config);
+        let producer: FutureProducer = client_config.create()?;
 
-        Ok(Self { producer })
+        Ok(Self { producer, partition_count: cfg.kafka_partition_count })
     }
 
+    #[tracing::instrument(skip(self, message), fields(message_id = %message.message_id))]
     pub async fn send_discord_message(&self, message: KafkaMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let topic = DISCORD_MESSAGES_TOPIC;
         let key = message.get_partition_key();
+        let partition = consistent_hash_partition(
+            &partition_hash_key(&message.guild_id, &message.channel_id),
+            self.partition_count,
+        );
         let payload = serde_json::to_vec(&message)?;
+        let headers = telemetry::inject_trace_context(&tracing::Span::current());
 
         let record = FutureRecord::to(topic)
             .key(&key)
-            .payload(&payload);
+            .partition(partition)
+            .payload(&payload)
+            .headers(headers);
 
         match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => {
-                info!(topic = topic, key = %key, "Sent Discord message to Kafka");
+                info!(topic = topic, key = %key, partition, "Sent Discord message to Kafka");
                 Ok(())
             }
             Err((kafka_error, _)) => {
@@ -47,8 +61,58 @@ impl KafkaProducer {
 
     // Additional producer methods for future Kafka consumer implementation:
     // - send_message_chunk
-    // - send_embedding_request  
+    // - send_embedding_request
     // - send_pinecone_upsert
     // - send_query_request
     // - send_message (generic)
 }
+
+/// Builds the key `consistent_hash_partition` hashes: `guild_id:channel_id`,
+/// or just `channel_id` when there's no guild (DMs) - the same guild-vs-DM
+/// split `KafkaMessage::get_partition_key` already uses for the record key.
+fn partition_hash_key(guild_id: &Option<String>, channel_id: &str) -> String {
+    match guild_id {
+        Some(guild_id) => format!("{guild_id}:{channel_id}"),
+        None => channel_id.to_string(),
+    }
+}
+
+/// Hashes `key` with a stable hash and reduces it modulo `partition_count`,
+/// so every message for a given channel always lands on the same partition -
+/// preserving the temporal ordering `ChunkManager` assumes per channel -
+/// while still spreading channels across all partitions. `DefaultHasher`'s
+/// keys are fixed rather than randomized per-process like `RandomState`, so
+/// this stays stable across producer restarts and replicas.
+fn consistent_hash_partition(key: &str, partition_count: u32) -> i32 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % partition_count.max(1) as u64) as i32
+}
+
+/// Thin [`MessageProducer`] wrapper around a `FutureProducer`, so callers
+/// that need the trait object (like `KafkaConsumer`'s response producer) can
+/// get one without duplicating the header-injection dance below.
+pub struct KafkaMessageProducer {
+    producer: FutureProducer,
+}
+
+impl KafkaMessageProducer {
+    pub fn new(producer: FutureProducer) -> Self {
+        Self { producer }
+    }
+}
+
+#[async_trait]
+impl MessageProducer for KafkaMessageProducer {
+    async fn send(&self, topic: &str, key: &str, payload: Vec<u8>) -> Result<(), DynErr> {
+        let headers = telemetry::inject_trace_context(&tracing::Span::current());
+        let record = FutureRecord::to(topic).key(key).payload(&payload).headers(headers);
+
+        self.producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map(|_| ())
+            .map_err(|(err, _)| err.into())
+    }
+}
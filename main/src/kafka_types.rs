@@ -1,28 +1,87 @@
 use serde::{Deserialize, Serialize};
-use crate::schema::{MessageEvent, MessageChunk};
+use serde_json::json;
+use crate::schema::{MessageEvent, MessageChunk, Platform, QueryFilters};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KafkaEventType {
     DiscordMessage,
+    DiscordMessageUpdate,
+    DiscordMessageDelete,
     MessageChunk,
     EmbeddingRequest,
     PineconeUpsert,
     QueryRequest,
+    QueryResponse,
+}
+
+/// One retrieved chunk that contributed to a `QueryResponse`'s answer, kept
+/// alongside its relevance score so the Discord-facing service can show its
+/// work (or a future caller can re-rank/audit it).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuerySource {
+    pub chunk_id: String,
+    pub score: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KafkaMessage {
     pub event_type: KafkaEventType,
     pub message_id: String,
+    /// Source platform this envelope originated from (Discord, Telegram, a
+    /// generic bridge, ...). Defaults to `Discord` so envelopes produced
+    /// before this field existed still deserialize.
+    #[serde(default)]
+    pub platform: Platform,
     pub guild_id: Option<String>,
     pub channel_id: String,
     pub timestamp: String,
     pub payload: KafkaPayload,
+    /// Retry/backoff bookkeeping for an envelope republished after a
+    /// transient downstream failure (embedding/Pinecone-upsert rate limits,
+    /// network errors). `None` means "never retried" - envelopes produced
+    /// before this field existed still deserialize.
+    #[serde(default)]
+    pub delivery: Option<DeliveryInfo>,
 }
 
+/// How many times an envelope has been (re)delivered, stamped by
+/// `KafkaMessage::into_retry` each time a downstream step fails it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeliveryInfo {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// RFC3339 timestamp of the first delivery attempt, kept constant
+    /// across retries so a consumer can measure total time-in-flight.
+    pub first_seen: String,
+    pub last_error: Option<String>,
+}
+
+impl DeliveryInfo {
+    fn first_attempt(max_attempts: u32) -> Self {
+        Self {
+            attempt: 1,
+            max_attempts,
+            first_seen: chrono::Utc::now().to_rfc3339(),
+            last_error: None,
+        }
+    }
+}
+
+/// Default retry budget `KafkaMessage::into_retry` gives an envelope that
+/// hasn't failed before. It is a fixed constant, separate from the
+/// `kafka_max_retries` setting that caps `KafkaConsumer::process_with_retry`'s
+/// in-process retries (default 3).
+pub const DEFAULT_MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum KafkaPayload {
     DiscordMessage(MessageEvent),
+    DiscordMessageUpdate(MessageEvent),
+    DiscordMessageDelete {
+        message_id: String,
+        channel_id: String,
+        guild_id: Option<String>,
+    },
     MessageChunk(MessageChunk),
     EmbeddingRequest {
         text: String,
@@ -37,7 +96,27 @@ pub enum KafkaPayload {
     QueryRequest {
         question: String,
         user_id: String,
+        /// Scopes the search to a guild; its absence means a DM search
+        /// keyed by `channel_id` instead.
         guild_id: Option<String>,
+        /// Restricts retrieval to messages from this platform; `None`
+        /// searches across all platforms.
+        #[serde(default)]
+        platform: Option<Platform>,
+        /// Structured metadata pre-filters mirroring Discord's
+        /// message-search parameters. Omitted from the serialized envelope
+        /// entirely when empty, so producers/consumers that predate this
+        /// field round-trip unchanged.
+        #[serde(default, skip_serializing_if = "QueryFilters::is_empty")]
+        filters: QueryFilters,
+    },
+    QueryResponse {
+        request_id: String,
+        question: String,
+        answer: String,
+        user_id: String,
+        guild_id: Option<String>,
+        sources: Vec<QuerySource>,
     },
 }
 
@@ -46,10 +125,81 @@ impl KafkaMessage {
         Self {
             event_type: KafkaEventType::DiscordMessage,
             message_id: message.id.clone(),
+            platform: message.platform,
             guild_id: message.guild_id.clone(),
             channel_id: message.channel_id.clone(),
             timestamp: message.timestamp.clone(),
             payload: KafkaPayload::DiscordMessage(message),
+            delivery: None,
+        }
+    }
+
+    /// Builds the envelope for an edited Discord (or bridge/Telegram)
+    /// message, carrying the new `MessageEvent` content so a consumer can
+    /// re-chunk and re-embed it the same way it would a fresh message.
+    pub fn new_message_update(message: MessageEvent) -> Self {
+        Self {
+            event_type: KafkaEventType::DiscordMessageUpdate,
+            message_id: message.id.clone(),
+            platform: message.platform,
+            guild_id: message.guild_id.clone(),
+            channel_id: message.channel_id.clone(),
+            timestamp: message.timestamp.clone(),
+            payload: KafkaPayload::DiscordMessageUpdate(message),
+            delivery: None,
+        }
+    }
+
+    /// Builds the envelope for a deleted message. Unlike `new_message_update`
+    /// this carries no content, only enough to locate and tombstone the
+    /// derived chunk/embedding IDs a consumer produced for it.
+    pub fn new_message_delete(
+        message_id: String,
+        platform: Platform,
+        channel_id: String,
+        guild_id: Option<String>,
+        timestamp: String,
+    ) -> Self {
+        Self {
+            event_type: KafkaEventType::DiscordMessageDelete,
+            message_id: message_id.clone(),
+            platform,
+            guild_id: guild_id.clone(),
+            channel_id: channel_id.clone(),
+            timestamp,
+            payload: KafkaPayload::DiscordMessageDelete { message_id, channel_id, guild_id },
+            delivery: None,
+        }
+    }
+
+    /// Builds the response to a `QueryRequest`, keyed by `request_id` (the
+    /// original request's `message_id`) so the Discord-facing service can
+    /// match it back up to the interaction that asked the question.
+    pub fn new_query_response(
+        request_id: String,
+        channel_id: String,
+        guild_id: Option<String>,
+        question: String,
+        answer: String,
+        user_id: String,
+        sources: Vec<QuerySource>,
+    ) -> Self {
+        Self {
+            event_type: KafkaEventType::QueryResponse,
+            message_id: request_id.clone(),
+            platform: Platform::default(),
+            guild_id: guild_id.clone(),
+            channel_id,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            payload: KafkaPayload::QueryResponse {
+                request_id,
+                question,
+                answer,
+                user_id,
+                guild_id,
+                sources,
+            },
+            delivery: None,
         }
     }
 
@@ -59,16 +209,243 @@ impl KafkaMessage {
     // - new_pinecone_upsert
     // - new_query_request
 
+    /// Stamps this envelope for redelivery after a transient downstream
+    /// failure (embedding/Pinecone-upsert rate limits, network errors) and
+    /// returns the topic a producer should republish it to: the same
+    /// `DISCORD_MESSAGES_TOPIC` while attempts remain under the budget, or
+    /// `DISCORD_MESSAGES_DLQ_TOPIC` once it's exhausted. Initializes
+    /// `delivery` with `DEFAULT_MAX_DELIVERY_ATTEMPTS` if this is the
+    /// envelope's first failure, mirroring `new_discord_message` always
+    /// starting one with `delivery: None`.
+    pub fn into_retry(mut self, err: String) -> (Self, &'static str) {
+        let delivery = self.delivery.get_or_insert_with(|| DeliveryInfo::first_attempt(DEFAULT_MAX_DELIVERY_ATTEMPTS));
+        delivery.attempt += 1;
+        delivery.last_error = Some(err);
+
+        let topic = if delivery.attempt <= delivery.max_attempts {
+            DISCORD_MESSAGES_TOPIC
+        } else {
+            DISCORD_MESSAGES_DLQ_TOPIC
+        };
+
+        (self, topic)
+    }
+
     pub fn get_partition_key(&self) -> String {
         match &self.guild_id {
             Some(guild_id) => guild_id.clone(),
             None => format!("dm:{}", self.channel_id),
         }
     }
+
+    /// Serializes this envelope the way a CloudEvents-aware Kafka consumer
+    /// expects (https://github.com/cloudevents/spec/blob/v1.0/spec.md), so
+    /// `KafkaMessage` can interoperate with tooling/consumers outside this
+    /// codebase that only understand the CloudEvents wire format. This is an
+    /// alternate transport, not the default one - `KafkaProducer::send_discord_message`
+    /// still writes plain `KafkaMessage` JSON.
+    pub fn to_kafka_record(&self, mode: CloudEventsMode) -> Result<KafkaRecordParts, CloudEventsError> {
+        let key = self.get_partition_key();
+        let id = self.message_id.clone();
+        let ty = self.event_type.cloudevents_type().to_string();
+        let source = cloudevents_source(&self.guild_id, &self.channel_id);
+        let time = self.timestamp.clone();
+
+        match mode {
+            CloudEventsMode::Binary => {
+                let body = serde_json::to_vec(&self.payload)?;
+                let headers = vec![
+                    ("ce_specversion".to_string(), "1.0".to_string()),
+                    ("ce_id".to_string(), id),
+                    ("ce_type".to_string(), ty),
+                    ("ce_source".to_string(), source),
+                    ("ce_time".to_string(), time),
+                    ("content-type".to_string(), "application/json".to_string()),
+                ];
+                Ok(KafkaRecordParts { key: Some(key), headers, body })
+            }
+            CloudEventsMode::Structured => {
+                let envelope = json!({
+                    "specversion": "1.0",
+                    "id": id,
+                    "type": ty,
+                    "source": source,
+                    "time": time,
+                    "datacontenttype": "application/json",
+                    "data": self.payload,
+                });
+                let body = serde_json::to_vec(&envelope)?;
+                let headers = vec![
+                    ("content-type".to_string(), "application/cloudevents+json".to_string()),
+                ];
+                Ok(KafkaRecordParts { key: Some(key), headers, body })
+            }
+        }
+    }
+
+    /// Reconstructs a `KafkaMessage` from `to_kafka_record`'s wire format,
+    /// detecting binary vs structured mode the way the CloudEvents Kafka
+    /// protocol binding does: a `ce_specversion` header means binary, a
+    /// `content-type: application/cloudevents+json` body means structured.
+    /// Rejects records missing any required CloudEvents attribute
+    /// (`specversion`, `id`, `type`, `source`) instead of guessing.
+    pub fn from_kafka_record(headers: &[(String, String)], body: &[u8]) -> Result<Self, CloudEventsError> {
+        let header = |name: &str| headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+
+        if header("content-type") == Some("application/cloudevents+json") {
+            let envelope: serde_json::Value = serde_json::from_slice(body)?;
+            let specversion = envelope["specversion"].as_str().ok_or(CloudEventsError::MissingAttribute("specversion"))?;
+            if specversion != "1.0" {
+                return Err(CloudEventsError::MissingAttribute("specversion"));
+            }
+            let id = envelope["id"].as_str().ok_or(CloudEventsError::MissingAttribute("id"))?.to_string();
+            let ty = envelope["type"].as_str().ok_or(CloudEventsError::MissingAttribute("type"))?;
+            let source = envelope["source"].as_str().ok_or(CloudEventsError::MissingAttribute("source"))?;
+            let time = envelope["time"].as_str().unwrap_or_default().to_string();
+            let event_type = KafkaEventType::from_cloudevents_type(ty)
+                .ok_or_else(|| CloudEventsError::UnknownEventType(ty.to_string()))?;
+            let payload: KafkaPayload = serde_json::from_value(envelope["data"].clone())?;
+            let (guild_id, channel_id) = parse_cloudevents_source(source);
+
+            Ok(Self { event_type, message_id: id, platform: Platform::default(), guild_id, channel_id, timestamp: time, payload, delivery: None })
+        } else {
+            let specversion = header("ce_specversion").ok_or(CloudEventsError::MissingAttribute("ce_specversion"))?;
+            if specversion != "1.0" {
+                return Err(CloudEventsError::MissingAttribute("ce_specversion"));
+            }
+            let id = header("ce_id").ok_or(CloudEventsError::MissingAttribute("ce_id"))?.to_string();
+            let ty = header("ce_type").ok_or(CloudEventsError::MissingAttribute("ce_type"))?;
+            let source = header("ce_source").ok_or(CloudEventsError::MissingAttribute("ce_source"))?;
+            let time = header("ce_time").unwrap_or_default().to_string();
+            let event_type = KafkaEventType::from_cloudevents_type(ty)
+                .ok_or_else(|| CloudEventsError::UnknownEventType(ty.to_string()))?;
+            let payload: KafkaPayload = serde_json::from_slice(body)?;
+            let (guild_id, channel_id) = parse_cloudevents_source(source);
+
+            Ok(Self { event_type, message_id: id, platform: Platform::default(), guild_id, channel_id, timestamp: time, payload, delivery: None })
+        }
+    }
+}
+
+impl KafkaEventType {
+    /// The reverse-DNS `ce_type`/`type` CloudEvents attribute for this event,
+    /// namespaced under `com.quiry` the way CloudEvents producers are
+    /// expected to namespace their event types.
+    fn cloudevents_type(&self) -> &'static str {
+        match self {
+            KafkaEventType::DiscordMessage => "com.quiry.discord.message",
+            KafkaEventType::DiscordMessageUpdate => "com.quiry.discord.message_update",
+            KafkaEventType::DiscordMessageDelete => "com.quiry.discord.message_delete",
+            KafkaEventType::MessageChunk => "com.quiry.discord.message_chunk",
+            KafkaEventType::EmbeddingRequest => "com.quiry.embedding.request",
+            KafkaEventType::PineconeUpsert => "com.quiry.pinecone.upsert",
+            KafkaEventType::QueryRequest => "com.quiry.query.request",
+            KafkaEventType::QueryResponse => "com.quiry.query.response",
+        }
+    }
+
+    fn from_cloudevents_type(value: &str) -> Option<Self> {
+        match value {
+            "com.quiry.discord.message" => Some(KafkaEventType::DiscordMessage),
+            "com.quiry.discord.message_update" => Some(KafkaEventType::DiscordMessageUpdate),
+            "com.quiry.discord.message_delete" => Some(KafkaEventType::DiscordMessageDelete),
+            "com.quiry.discord.message_chunk" => Some(KafkaEventType::MessageChunk),
+            "com.quiry.embedding.request" => Some(KafkaEventType::EmbeddingRequest),
+            "com.quiry.pinecone.upsert" => Some(KafkaEventType::PineconeUpsert),
+            "com.quiry.query.request" => Some(KafkaEventType::QueryRequest),
+            "com.quiry.query.response" => Some(KafkaEventType::QueryResponse),
+            _ => None,
+        }
+    }
+}
+
+/// The CloudEvents `source` attribute for a `(guild_id, channel_id)` pair,
+/// mirroring `KafkaMessage::get_partition_key`'s guild-vs-DM split as a URI
+/// path instead of a flat key.
+fn cloudevents_source(guild_id: &Option<String>, channel_id: &str) -> String {
+    match guild_id {
+        Some(guild_id) => format!("/guilds/{guild_id}/channels/{channel_id}"),
+        None => format!("/channels/{channel_id}"),
+    }
+}
+
+/// Inverse of `cloudevents_source`. Anything that doesn't match the
+/// `/guilds/{id}/channels/{id}` or `/channels/{id}` shape (e.g. a `source`
+/// from a non-Quiry CloudEvents producer) is kept verbatim as `channel_id`
+/// with no guild, rather than rejected outright.
+fn parse_cloudevents_source(source: &str) -> (Option<String>, String) {
+    if let Some(rest) = source.strip_prefix("/guilds/") {
+        if let Some((guild_id, channel_id)) = rest.split_once("/channels/") {
+            return (Some(guild_id.to_string()), channel_id.to_string());
+        }
+    }
+    if let Some(channel_id) = source.strip_prefix("/channels/") {
+        return (None, channel_id.to_string());
+    }
+    (None, source.to_string())
+}
+
+/// Which CloudEvents content mode `KafkaMessage::to_kafka_record` should
+/// use: `Binary` maps context attributes to `ce_`-prefixed headers and
+/// leaves `KafkaPayload`'s JSON as the body; `Structured` bundles
+/// attributes and data into one `application/cloudevents+json` body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudEventsMode {
+    Binary,
+    Structured,
+}
+
+/// The pieces of a Kafka record `to_kafka_record`/`from_kafka_record` work
+/// with, decoupled from `rdkafka`'s borrowed `FutureRecord`/`OwnedHeaders`
+/// types so this module doesn't need an `rdkafka` dependency just to build
+/// an envelope.
+#[derive(Debug, Clone)]
+pub struct KafkaRecordParts {
+    pub key: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Errors from reconstructing a `KafkaMessage` out of a CloudEvents-wrapped
+/// Kafka record.
+#[derive(Debug)]
+pub enum CloudEventsError {
+    /// A required CloudEvents context attribute (`specversion`, `id`,
+    /// `type`, or `source`) was missing, or `specversion` was present but
+    /// not `"1.0"`.
+    MissingAttribute(&'static str),
+    /// The `type` attribute didn't match any of this crate's known
+    /// `KafkaEventType`s.
+    UnknownEventType(String),
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for CloudEventsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloudEventsError::MissingAttribute(attr) => write!(f, "missing required CloudEvents attribute: {attr}"),
+            CloudEventsError::UnknownEventType(ty) => write!(f, "unknown CloudEvents type: {ty}"),
+            CloudEventsError::Json(err) => write!(f, "CloudEvents JSON error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CloudEventsError {}
+
+impl From<serde_json::Error> for CloudEventsError {
+    fn from(err: serde_json::Error) -> Self {
+        CloudEventsError::Json(err)
+    }
 }
 
 // Topic names
 pub const DISCORD_MESSAGES_TOPIC: &str = "discord-messages";
+/// Where `KafkaMessage::into_retry` routes an envelope once it's exhausted
+/// `delivery.max_attempts`, separate from `DlqProducer`'s per-offset
+/// `<topic><dlq_suffix>` DLQ which captures messages the consumer itself
+/// couldn't deserialize or validate, or that still failed after its
+/// in-process retries.
+pub const DISCORD_MESSAGES_DLQ_TOPIC: &str = "discord-messages-dlq";
 // Additional topics for future Kafka consumer implementation:
 // pub const MESSAGE_CHUNKS_TOPIC: &str = "message-chunks";
 // pub const EMBEDDING_REQUESTS_TOPIC: &str = "embedding-requests";
@@ -0,0 +1,23 @@
+pub mod config;
+pub mod schema;
+pub mod http_client;
+pub mod cohere;
+pub mod pinecone;
+pub mod elasticsearch;
+pub mod chunking;
+pub mod search;
+pub mod subscribe;
+pub mod ingest;
+pub mod hnsw;
+pub mod vector_store;
+pub mod handler;
+pub mod message_source;
+pub mod telegram;
+pub mod broker;
+pub mod kafka_types;
+pub mod kafka_producer;
+pub mod kafka_consumer;
+pub mod dlq;
+pub mod metrics;
+pub mod health;
+pub mod telemetry;
@@ -1,14 +1,13 @@
 use dotenv::dotenv;
 use serenity::prelude::*;
-use tracing_subscriber;
-use Quiry::{config::Config, handler::Handler};
+use Quiry::{config::Config, handler::Handler, telemetry};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    tracing_subscriber::fmt::init();
 
     let cfg = Config::from_env();
+    telemetry::init_tracing(&cfg);
 
     let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
     let mut handler = Handler::new(cfg).expect("Failed to create handler");
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use crate::schema::{MessageEvent, Platform};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Abstracts over "something that produces [`MessageEvent`]s" so the rest
+/// of the pipeline (chunking -> embeddings -> Pinecone/ES, via
+/// `process_message_directly` or Kafka) never needs to know whether a
+/// message came from Discord's gateway, a Telegram long-poll, or a generic
+/// webhook bridge, the same way [`crate::vector_store::VectorStore`]
+/// abstracts over where vectors are stored.
+#[async_trait]
+pub trait MessageSource: Send + Sync {
+    /// The platform this source produces events for; stamped onto every
+    /// [`MessageEvent`] it returns.
+    fn platform(&self) -> Platform;
+
+    /// Blocks until at least one new message is available (or the source's
+    /// own poll/long-poll interval elapses with none), then returns
+    /// whatever arrived: an empty `Vec` with `Ok` when the interval
+    /// elapsed without new messages.
+    async fn poll(&self) -> Result<Vec<MessageEvent>, DynErr>;
+}
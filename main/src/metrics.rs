@@ -1,4 +1,4 @@
-use prometheus::{Counter, Histogram, Gauge, Registry, TextEncoder, HistogramOpts, Opts};
+use prometheus::{Counter, CounterVec, Histogram, Gauge, GaugeVec, Registry, TextEncoder, HistogramOpts, Opts};
 use tracing::error;
 
 lazy_static::lazy_static! {
@@ -31,6 +31,10 @@ lazy_static::lazy_static! {
     pub static ref DISCORD_API_DURATION: Histogram = Histogram::with_opts(
         HistogramOpts::new("quiry_discord_api_duration_seconds", "Time spent on Discord API calls")
     ).unwrap();
+
+    pub static ref COHERE_CHAT_DURATION: Histogram = Histogram::with_opts(
+        HistogramOpts::new("quiry_cohere_chat_duration_seconds", "Time spent on Cohere chat/generation calls")
+    ).unwrap();
     
     // Kafka metrics
     pub static ref KAFKA_MESSAGES_SENT: Counter = Counter::with_opts(
@@ -40,7 +44,26 @@ lazy_static::lazy_static! {
     pub static ref KAFKA_MESSAGES_RECEIVED: Counter = Counter::with_opts(
         Opts::new("quiry_kafka_messages_received_total", "Total number of messages received from Kafka")
     ).unwrap();
-    
+
+    // Dead-letter queue metrics, labeled by source topic (and failure kind where useful)
+    pub static ref DLQ_MESSAGES_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("quiry_dlq_messages_total", "Total number of messages sent to the dead-letter queue, by source topic and failure kind"),
+        &["topic", "kind"]
+    ).unwrap();
+
+    pub static ref DLQ_SEND_FAILURES_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("quiry_dlq_send_failures_total", "Total number of failures publishing to the dead-letter queue, by source topic"),
+        &["topic"]
+    ).unwrap();
+
+    pub static ref MESSAGES_INVALID: Counter = Counter::with_opts(
+        Opts::new("quiry_messages_invalid_total", "Total number of messages that failed JSON Schema validation")
+    ).unwrap();
+
+    pub static ref QUERY_RESPONSE_LATENCY: Histogram = Histogram::with_opts(
+        HistogramOpts::new("quiry_query_response_latency_seconds", "End-to-end time from consuming a QueryRequest to publishing its QueryResponse")
+    ).unwrap();
+
     // Search metrics
     pub static ref SEARCH_REQUESTS: Counter = Counter::with_opts(
         Opts::new("quiry_search_requests_total", "Total number of search requests")
@@ -49,7 +72,22 @@ lazy_static::lazy_static! {
     pub static ref SEARCH_DURATION: Histogram = Histogram::with_opts(
         HistogramOpts::new("quiry_search_duration_seconds", "Time spent on search operations")
     ).unwrap();
-    
+
+    pub static ref HYBRID_SEARCH_DURATION: Histogram = Histogram::with_opts(
+        HistogramOpts::new("quiry_hybrid_search_duration_seconds", "Time spent in hybrid_search, including both retrievals and the fusion")
+    ).unwrap();
+
+    // HTTP resiliency metrics (retries / circuit breaker), labeled by backend
+    pub static ref HTTP_RETRIES_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("quiry_http_retries_total", "Total number of retried outbound HTTP requests, by backend"),
+        &["backend"]
+    ).unwrap();
+
+    pub static ref CIRCUIT_BREAKER_STATE: GaugeVec = GaugeVec::new(
+        Opts::new("quiry_circuit_breaker_state", "Circuit breaker state by backend (0=closed, 1=open, 2=half-open)"),
+        &["backend"]
+    ).unwrap();
+
     // Health metrics
     pub static ref ACTIVE_CONNECTIONS: Gauge = Gauge::with_opts(
         Opts::new("quiry_active_connections", "Number of active connections")
@@ -76,10 +114,18 @@ impl MetricsRegistry {
         registry.register(Box::new(PINECONE_UPSERT_DURATION.clone())).unwrap();
         registry.register(Box::new(ELASTICSEARCH_INDEX_DURATION.clone())).unwrap();
         registry.register(Box::new(DISCORD_API_DURATION.clone())).unwrap();
+        registry.register(Box::new(COHERE_CHAT_DURATION.clone())).unwrap();
         registry.register(Box::new(KAFKA_MESSAGES_SENT.clone())).unwrap();
         registry.register(Box::new(KAFKA_MESSAGES_RECEIVED.clone())).unwrap();
+        registry.register(Box::new(DLQ_MESSAGES_TOTAL.clone())).unwrap();
+        registry.register(Box::new(DLQ_SEND_FAILURES_TOTAL.clone())).unwrap();
+        registry.register(Box::new(MESSAGES_INVALID.clone())).unwrap();
+        registry.register(Box::new(QUERY_RESPONSE_LATENCY.clone())).unwrap();
         registry.register(Box::new(SEARCH_REQUESTS.clone())).unwrap();
         registry.register(Box::new(SEARCH_DURATION.clone())).unwrap();
+        registry.register(Box::new(HYBRID_SEARCH_DURATION.clone())).unwrap();
+        registry.register(Box::new(HTTP_RETRIES_TOTAL.clone())).unwrap();
+        registry.register(Box::new(CIRCUIT_BREAKER_STATE.clone())).unwrap();
         registry.register(Box::new(ACTIVE_CONNECTIONS.clone())).unwrap();
         registry.register(Box::new(MEMORY_USAGE.clone())).unwrap();
         
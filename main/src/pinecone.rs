@@ -1,49 +1,158 @@
-use reqwest::Client;
 use serde_json::json;
 use tracing::{info, error};
-use crate::{config::Config, schema::{MessageEvent, QueryResult, MessageChunk, ChunkQueryResult}};
+use crate::{
+    config::Config,
+    schema::{MessageEvent, QueryResult, MessageChunk, ChunkQueryResult, HistoryMessage, HistoryPage, HistorySelector, Platform, QueryFilters},
+    cohere::{get_embedding, EmbeddingInputType},
+    http_client::{shared_client, with_retry, RetryConfig, PINECONE_BREAKER},
+    metrics::PINECONE_UPSERT_DURATION,
+    subscribe::INGESTION_SEQ,
+};
 
 type DynErr = Box<dyn std::error::Error + Send + Sync>;
 
+#[tracing::instrument(skip(cfg, msg, embedding), fields(message_id = %msg.id))]
 pub async fn upsert_to_pinecone(cfg: &Config, msg: &MessageEvent, embedding: Vec<f32>) -> Result<(), DynErr> {
     let url = format!("{}/vectors/upsert", cfg.pinecone_host);
-    let client = Client::new();
-
-    let res = client
-        .post(&url)
-        .header("Api-Key", &cfg.pinecone_key)
-        .json(&json!({
-            "namespace": cfg.namespace,
-            "vectors": [{
+    let seq = INGESTION_SEQ.next_seq();
+    let body = json!({
+        "namespace": cfg.namespace,
+        "vectors": [{
+            "id": msg.id,
+            "values": embedding,
+            "metadata": {
+                "platform": msg.platform,
+                "guild_id": msg.guild_id,
+                "channel_id": msg.channel_id,
+                "author_id": msg.author_id,
+                "timestamp": msg.timestamp,
+                "text": msg.text,
+                "seq": seq
+            }
+        }]
+    });
+
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&body).send()
+    }).await?;
+
+    let status = res.status();
+    let resp_body = res.text().await?;
+
+    if !status.is_success() {
+        error!(status=?status, body=?resp_body, "Pinecone upsert failed");
+        return Err(format!("Pinecone error: {status}").into());
+    }
+
+    info!(msg_id=?msg.id, "Upserted to Pinecone");
+    Ok(())
+}
+
+/// Upserts a batch of vectors in a single request. The caller is
+/// responsible for keeping `items` within Pinecone's request-size limit
+/// (~2MB / 100 vectors); see `ingest::compute_chunk_size` for how the
+/// batching layer picks a safe chunk size. Returns the ids that failed to
+/// upsert so a partial failure doesn't need to fail the whole batch.
+pub async fn upsert_batch_to_pinecone(cfg: &Config, items: &[(MessageEvent, Vec<f32>)]) -> Result<Vec<String>, DynErr> {
+    if items.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let url = format!("{}/vectors/upsert", cfg.pinecone_host);
+
+    let vectors: Vec<_> = items
+        .iter()
+        .map(|(msg, embedding)| {
+            json!({
                 "id": msg.id,
                 "values": embedding,
                 "metadata": {
+                    "platform": msg.platform,
                     "guild_id": msg.guild_id,
                     "channel_id": msg.channel_id,
                     "author_id": msg.author_id,
                     "timestamp": msg.timestamp,
-                    "text": msg.text
+                    "text": msg.text,
+                    "seq": INGESTION_SEQ.next_seq()
                 }
-            }]
-        }))
-        .send()
-        .await?;
+            })
+        })
+        .collect();
+
+    let body = json!({
+        "namespace": cfg.namespace,
+        "vectors": vectors
+    });
+
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&body).send()
+    }).await?;
 
     let status = res.status();
-    let body = res.text().await?;
+    let resp_body = res.text().await?;
 
     if !status.is_success() {
-        error!(status=?status, body=?body, "Pinecone upsert failed");
-        return Err(format!("Pinecone error: {status}").into());
+        error!(status=?status, body=?resp_body, count=items.len(), "Pinecone batch upsert failed");
+        return Ok(items.iter().map(|(msg, _)| msg.id.clone()).collect());
     }
 
-    info!(msg_id=?msg.id, "Upserted to Pinecone");
-    Ok(())
+    info!(count = items.len(), "Upserted batch to Pinecone");
+    Ok(vec![])
+}
+
+/// Layers `QueryFilters`' metadata pre-filters onto an already-built Pinecone
+/// `filter` object for individual-message queries, mirroring the
+/// `guild_id`/`platform` `$eq`/`$exists` clauses each caller assembles
+/// first. `has`/`mentions`/`pinned` have no corresponding ingested metadata
+/// field yet (the upsert path never stores link/embed/attachment/mention/
+/// pinned flags), so there's nothing in the namespace to filter on for
+/// them - they're accepted on `QueryFilters` but have no effect here until
+/// ingestion starts writing that metadata.
+fn apply_message_filters(filter: &mut serde_json::Value, filters: &QueryFilters) {
+    if let Some(ref author_id) = filters.author_id {
+        filter["author_id"] = json!({"$eq": author_id});
+    }
+    if !filters.channel_ids.is_empty() {
+        filter["channel_id"] = json!({"$in": filters.channel_ids});
+    }
+    if filters.min_timestamp.is_some() || filters.max_timestamp.is_some() {
+        let mut range = serde_json::Map::new();
+        if let Some(ref min_ts) = filters.min_timestamp {
+            range.insert("$gte".to_string(), json!(min_ts));
+        }
+        if let Some(ref max_ts) = filters.max_timestamp {
+            range.insert("$lte".to_string(), json!(max_ts));
+        }
+        filter["timestamp"] = serde_json::Value::Object(range);
+    }
 }
 
-pub async fn query_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<QueryResult>, DynErr> {
+/// Same idea as [`apply_message_filters`], but for chunk metadata, which
+/// shapes these fields differently: `upsert_chunk_to_pinecone` stores every
+/// author folded into the chunk as `authors` (plural array) rather than a
+/// single `author_id`, and spans a range (`first_timestamp`..`last_timestamp`)
+/// rather than one `timestamp`. `author_id` is applied to the `authors`
+/// array field with `$in` (Pinecone's array-membership check - `$eq` only
+/// matches an exact whole-array equality, which would never match a
+/// multi-author chunk); the timestamp bounds are applied as a range-overlap
+/// check against the chunk's span instead of a single-point comparison.
+fn apply_chunk_filters(filter: &mut serde_json::Value, filters: &QueryFilters) {
+    if let Some(ref author_id) = filters.author_id {
+        filter["authors"] = json!({"$in": [author_id]});
+    }
+    if !filters.channel_ids.is_empty() {
+        filter["channel_id"] = json!({"$in": filters.channel_ids});
+    }
+    if let Some(ref min_ts) = filters.min_timestamp {
+        filter["last_timestamp"] = json!({"$gte": min_ts});
+    }
+    if let Some(ref max_ts) = filters.max_timestamp {
+        filter["first_timestamp"] = json!({"$lte": max_ts});
+    }
+}
+
+pub async fn query_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>, platform: Option<Platform>, filters: &QueryFilters) -> Result<Vec<QueryResult>, DynErr> {
     let url = format!("{}/query", cfg.pinecone_host);
-    let client = Client::new();
 
     let mut query = json!({
         "namespace": cfg.namespace,
@@ -54,23 +163,22 @@ pub async fn query_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, gui
     });
 
     // Add guild_id filter if provided
+    let mut filter = serde_json::Map::new();
     if let Some(guild_id) = guild_id {
-        query["filter"] = json!({
-            "guild_id": {"$eq": guild_id}
-        });
+        filter.insert("guild_id".to_string(), json!({"$eq": guild_id}));
     } else {
         // For DMs, filter for messages without guild_id (null values)
-        query["filter"] = json!({
-            "guild_id": {"$exists": false}
-        });
+        filter.insert("guild_id".to_string(), json!({"$exists": false}));
+    }
+    if let Some(platform) = platform {
+        filter.insert("platform".to_string(), json!({"$eq": platform}));
     }
+    query["filter"] = serde_json::Value::Object(filter);
+    apply_message_filters(&mut query["filter"], filters);
 
-    let res = client
-        .post(&url)
-        .header("Api-Key", &cfg.pinecone_key)
-        .json(&query)
-        .send()
-        .await?;
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&query).send()
+    }).await?;
 
     let status = res.status();
     let body: serde_json::Value = res.json().await?;
@@ -98,6 +206,7 @@ pub async fn query_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, gui
                 author_id: author_id.to_string(),
                 timestamp: timestamp.to_string(),
                 score,
+                seq: metadata["seq"].as_u64().unwrap_or(0),
             });
         }
     }
@@ -108,11 +217,11 @@ pub async fn query_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, gui
 
 pub async fn upsert_chunk_to_pinecone(cfg: &Config, chunk: &MessageChunk, embedding: Vec<f32>) -> Result<(), DynErr> {
     let url = format!("{}/vectors/upsert", cfg.pinecone_host);
-    let client = Client::new();
 
     let mut metadata = json!({
         "type": "chunk",
         "chunk_id": chunk.chunk_id,
+        "platform": chunk.platform,
         "guild_id": chunk.guild_id,
         "channel_id": chunk.channel_id,
         "first_msg_id": chunk.first_msg_id,
@@ -130,25 +239,24 @@ pub async fn upsert_chunk_to_pinecone(cfg: &Config, chunk: &MessageChunk, embedd
         metadata["summary"] = json!(summary);
     }
 
-    let res = client
-        .post(&url)
-        .header("Api-Key", &cfg.pinecone_key)
-        .json(&json!({
-            "namespace": cfg.namespace,
-            "vectors": [{
-                "id": format!("chunk_{}", chunk.chunk_id),
-                "values": embedding,
-                "metadata": metadata
-            }]
-        }))
-        .send()
-        .await?;
+    let body = json!({
+        "namespace": cfg.namespace,
+        "vectors": [{
+            "id": format!("chunk_{}", chunk.chunk_id),
+            "values": embedding,
+            "metadata": metadata
+        }]
+    });
+
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&body).send()
+    }).await?;
 
     let status = res.status();
-    let body = res.text().await?;
+    let resp_body = res.text().await?;
 
     if !status.is_success() {
-        error!(status=?status, body=?body, "Pinecone chunk upsert failed");
+        error!(status=?status, body=?resp_body, "Pinecone chunk upsert failed");
         return Err(format!("Pinecone chunk error: {status}").into());
     }
 
@@ -156,9 +264,9 @@ pub async fn upsert_chunk_to_pinecone(cfg: &Config, chunk: &MessageChunk, embedd
     Ok(())
 }
 
-pub async fn query_chunks_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<ChunkQueryResult>, DynErr> {
+#[tracing::instrument(skip(cfg, embedding), fields(top_k, guild_id = ?guild_id, platform = ?platform))]
+pub async fn query_chunks_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>, platform: Option<Platform>, filters: &QueryFilters) -> Result<Vec<ChunkQueryResult>, DynErr> {
     let url = format!("{}/query", cfg.pinecone_host);
-    let client = Client::new();
 
     let mut query = json!({
         "namespace": cfg.namespace,
@@ -178,13 +286,14 @@ pub async fn query_chunks_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usi
         // For DMs, filter for chunks without guild_id (null values)
         query["filter"]["guild_id"] = json!({"$exists": false});
     }
+    if let Some(platform) = platform {
+        query["filter"]["platform"] = json!({"$eq": platform});
+    }
+    apply_chunk_filters(&mut query["filter"], filters);
 
-    let res = client
-        .post(&url)
-        .header("Api-Key", &cfg.pinecone_key)
-        .json(&query)
-        .send()
-        .await?;
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&query).send()
+    }).await?;
 
     let status = res.status();
     let body: serde_json::Value = res.json().await?;
@@ -247,3 +356,114 @@ pub async fn query_chunks_pinecone(cfg: &Config, embedding: Vec<f32>, top_k: usi
     info!(count = results.len(), "Found similar chunks");
     Ok(results)
 }
+
+/// Best-effort `/history` fallback for when no ElasticSearch client is
+/// configured. Pinecone's `/query` endpoint has no native "sort by
+/// metadata" mode, so this runs a similarity query against a neutral
+/// embedding (relevance is irrelevant here, only the metadata filter
+/// matters) filtered by guild/channel/author, then windows and sorts the
+/// matches client-side by their `timestamp` metadata to approximate
+/// `ElasticsearchClient::query_history`'s selector semantics.
+pub async fn query_history_pinecone(
+    cfg: &Config,
+    selector: &HistorySelector,
+    guild_id: Option<String>,
+    channel_id: Option<&str>,
+    author_id: Option<&str>,
+    limit: usize,
+) -> Result<HistoryPage, DynErr> {
+    let url = format!("{}/query", cfg.pinecone_host);
+
+    let neutral_embedding = get_embedding(cfg, "history", EmbeddingInputType::SearchQuery).await?;
+
+    let mut filter = serde_json::Map::new();
+    if let Some(ref guild_id) = guild_id {
+        filter.insert("guild_id".to_string(), json!({"$eq": guild_id}));
+    } else {
+        filter.insert("guild_id".to_string(), json!({"$exists": false}));
+    }
+    if let Some(channel_id) = channel_id {
+        filter.insert("channel_id".to_string(), json!({"$eq": channel_id}));
+    }
+    if let Some(author_id) = author_id {
+        filter.insert("author_id".to_string(), json!({"$eq": author_id}));
+    }
+
+    // Pinecone's topK caps what a single query can return; fetch generously
+    // so the client-side timestamp window below has enough candidates.
+    let fetch_k = (limit * 4).clamp(50, 1000);
+
+    let query = json!({
+        "namespace": cfg.namespace,
+        "vector": neutral_embedding,
+        "topK": fetch_k,
+        "includeMetadata": true,
+        "includeValues": false,
+        "filter": filter
+    });
+
+    let res = with_retry(&PINECONE_BREAKER, &PINECONE_UPSERT_DURATION, &RetryConfig::default(), || {
+        shared_client().post(&url).header("Api-Key", &cfg.pinecone_key).json(&query).send()
+    }).await?;
+
+    let status = res.status();
+    let body: serde_json::Value = res.json().await?;
+
+    if !status.is_success() {
+        error!(status=?status, body=?body, "Pinecone history query failed");
+        return Err(format!("Pinecone history query error: {status}").into());
+    }
+
+    let empty_vec = vec![];
+    let matches = body["matches"].as_array().unwrap_or(&empty_vec);
+
+    // Chunks live in the same namespace but have `full_text`/no `text` key,
+    // so requiring `text` here naturally excludes them without a metadata
+    // filter on Pinecone's "$ne on a possibly-missing field" semantics.
+    let mut messages: Vec<HistoryMessage> = matches.iter().filter_map(|match_obj| {
+        let metadata = &match_obj["metadata"];
+        Some(HistoryMessage {
+            text: metadata["text"].as_str()?.to_string(),
+            author_id: metadata["author_id"].as_str()?.to_string(),
+            channel_id: metadata["channel_id"].as_str()?.to_string(),
+            timestamp: metadata["timestamp"].as_str()?.to_string(),
+        })
+    }).collect();
+
+    messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    let windowed: Vec<HistoryMessage> = match selector {
+        HistorySelector::Latest => messages.into_iter().take(limit).collect(),
+        HistorySelector::Before(ts) => messages.into_iter().filter(|m| m.timestamp < *ts).take(limit).collect(),
+        HistorySelector::After(ts) => {
+            let mut after: Vec<HistoryMessage> = messages.into_iter().filter(|m| m.timestamp > *ts).collect();
+            after.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            after.truncate(limit);
+            after.reverse();
+            after
+        }
+        HistorySelector::Around(ts) => {
+            let half = (limit / 2).max(1);
+            let before: Vec<HistoryMessage> = messages.iter().cloned().filter(|m| m.timestamp < *ts).take(half).collect();
+            let mut after: Vec<HistoryMessage> = messages.iter().cloned().filter(|m| m.timestamp >= *ts).collect();
+            after.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            after.truncate(limit - half);
+
+            let mut combined = before;
+            combined.extend(after);
+            combined.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            combined.truncate(limit);
+            combined
+        }
+    };
+
+    // `windowed` is always newest-first; `After` pages forward, so its
+    // cursor must be the newest boundary in this page (`first()`), not the
+    // oldest row already shown.
+    let cursor = match selector {
+        HistorySelector::After(_) => windowed.first().map(|m| m.timestamp.clone()),
+        _ => windowed.last().map(|m| m.timestamp.clone()),
+    };
+    info!(count = windowed.len(), "Found history window via Pinecone fallback");
+    Ok(HistoryPage { messages: windowed, cursor })
+}
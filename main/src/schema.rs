@@ -1,8 +1,30 @@
 use serde::{Deserialize, Serialize};
 
+/// Which chat platform a [`MessageEvent`] originated from, so a single
+/// ingestion pipeline (chunking -> embeddings -> Pinecone/ES) can serve
+/// Discord, Telegram, and generic webhook "bridge" sources without the
+/// downstream stages needing to know which one produced a given message.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    Discord,
+    Telegram,
+    /// A generic HTTP/webhook source, for bridges that don't warrant a
+    /// dedicated adapter (e.g. a third-party chat export or relay).
+    Bridge,
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        Platform::Discord
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MessageEvent {
     pub id: String,
+    #[serde(default)]
+    pub platform: Platform,
     pub guild_id: Option<String>,
     pub channel_id: String,
     pub author_id: String,
@@ -16,11 +38,17 @@ pub struct QueryResult {
     pub author_id: String,
     pub timestamp: String,
     pub score: f64,
+    /// Ingestion sequence number assigned at upsert time; used by
+    /// `subscribe::poll_changes` to tell which matches are new since a
+    /// caller's cursor. 0 for backends that don't track it (e.g. HNSW).
+    pub seq: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MessageChunk {
     pub chunk_id: String,
+    #[serde(default)]
+    pub platform: Platform,
     pub guild_id: Option<String>,
     pub channel_id: String,
     pub first_msg_id: String,
@@ -45,3 +73,130 @@ pub struct ChunkQueryResult {
     pub last_timestamp: String,
     pub score: f64,
 }
+
+/// Selects which window of history `/history` fetches, mirroring IRC's
+/// CHATHISTORY command: `Latest` pages backward from now, `Before`/`After`
+/// page relative to an RFC3339 boundary timestamp, and `Around` centers the
+/// page on one.
+#[derive(Debug, Clone)]
+pub enum HistorySelector {
+    Latest,
+    Before(String),
+    After(String),
+    Around(String),
+}
+
+/// One message in a [`HistoryPage`], stripped down to what `/history`
+/// renders - no Pinecone/ES-specific fields like score or seq.
+#[derive(Debug, Clone)]
+pub struct HistoryMessage {
+    pub text: String,
+    pub author_id: String,
+    pub channel_id: String,
+    pub timestamp: String,
+}
+
+/// A page of [`HistorySelector`] results, newest-first, returned by
+/// `ElasticsearchClient::query_history` (or its Pinecone-metadata fallback).
+/// `cursor` is the boundary timestamp for the next page: for `After` it is
+/// the newest message's timestamp and the next page is `after: cursor`; for
+/// the other selectors it is the oldest message's timestamp and the next
+/// page is `before: cursor`.
+#[derive(Debug, Clone)]
+pub struct HistoryPage {
+    pub messages: Vec<HistoryMessage>,
+    pub cursor: Option<String>,
+}
+
+/// Mirrors Discord message search's `has:` operator, restricting matches to
+/// messages that carry a link, embed, or attachment.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HasFilter {
+    Link,
+    Embed,
+    Attachment,
+}
+
+/// Structured retrieval filters modeled on Discord's message-search query
+/// parameters, so a `QueryRequest` can pre-filter the Pinecone namespace by
+/// metadata before ranking by embedding similarity instead of relying on
+/// semantic search alone. Every field is optional/empty by default and
+/// imposes no constraint when left that way; `channel_ids` is AND'd against
+/// the match when non-empty. `has`, `mentions` and `pinned` are carried and
+/// serialized but not applied by the Pinecone queries, since no matching
+/// metadata is stored at upsert time. The request's own `guild_id` (not a
+/// field here) scopes the search to a guild - its absence means a DM search
+/// keyed by `channel_id` instead.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct QueryFilters {
+    pub author_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channel_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub has: Vec<HasFilter>,
+    pub mentions: Option<String>,
+    pub min_timestamp: Option<String>,
+    pub max_timestamp: Option<String>,
+    pub pinned: Option<bool>,
+}
+
+impl QueryFilters {
+    pub fn builder() -> QueryFiltersBuilder {
+        QueryFiltersBuilder::default()
+    }
+
+    /// True when no field imposes a constraint, the case a `QueryRequest`
+    /// omits its `filters` entirely from the serialized envelope.
+    pub fn is_empty(&self) -> bool {
+        *self == QueryFilters::default()
+    }
+}
+
+/// Incrementally builds a [`QueryFilters`], mirroring how Discord's search
+/// UI accumulates `from:`/`has:`/`mentions:` terms one at a time.
+#[derive(Debug, Default)]
+pub struct QueryFiltersBuilder {
+    filters: QueryFilters,
+}
+
+impl QueryFiltersBuilder {
+    pub fn author_id(mut self, author_id: impl Into<String>) -> Self {
+        self.filters.author_id = Some(author_id.into());
+        self
+    }
+
+    pub fn channel_ids(mut self, channel_ids: Vec<String>) -> Self {
+        self.filters.channel_ids = channel_ids;
+        self
+    }
+
+    pub fn has(mut self, has: Vec<HasFilter>) -> Self {
+        self.filters.has = has;
+        self
+    }
+
+    pub fn mentions(mut self, mentions: impl Into<String>) -> Self {
+        self.filters.mentions = Some(mentions.into());
+        self
+    }
+
+    pub fn min_timestamp(mut self, min_timestamp: impl Into<String>) -> Self {
+        self.filters.min_timestamp = Some(min_timestamp.into());
+        self
+    }
+
+    pub fn max_timestamp(mut self, max_timestamp: impl Into<String>) -> Self {
+        self.filters.max_timestamp = Some(max_timestamp.into());
+        self
+    }
+
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.filters.pinned = Some(pinned);
+        self
+    }
+
+    pub fn build(self) -> QueryFilters {
+        self.filters
+    }
+}
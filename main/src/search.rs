@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use tracing::info;
+use crate::{
+    config::Config,
+    cohere::{get_embedding, EmbeddingInputType},
+    pinecone::query_chunks_pinecone,
+    elasticsearch::ElasticsearchClient,
+    metrics::HYBRID_SEARCH_DURATION,
+    schema::QueryFilters,
+};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Tuning knobs for [`hybrid_search`]. `k` is the RRF smoothing constant;
+/// the `*_depth` fields cap how many candidates each retriever contributes
+/// before fusion, and the `*_weight` fields let callers favor one retriever
+/// over the other.
+#[derive(Debug, Clone)]
+pub struct HybridSearchConfig {
+    pub k: f64,
+    pub pinecone_depth: usize,
+    pub es_depth: usize,
+    pub pinecone_weight: f64,
+    pub es_weight: f64,
+}
+
+impl Default for HybridSearchConfig {
+    fn default() -> Self {
+        Self {
+            k: 60.0,
+            pinecone_depth: 10,
+            es_depth: 10,
+            pinecone_weight: 1.0,
+            es_weight: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FusedResult {
+    pub text: String,
+    pub author_id: String,
+    pub timestamp: String,
+    pub score: f64,
+}
+
+/// Runs the Pinecone (semantic) and Elasticsearch (lexical) retrievers
+/// concurrently and fuses their rankings with Reciprocal Rank Fusion:
+/// `score = Σ weight_l / (k + rank_l(d))`, summed only over the lists that
+/// contain `d`. Results missing from one retriever simply contribute
+/// nothing for it, so the fused ranking is robust to either list being
+/// empty or the two score scales disagreeing.
+pub async fn hybrid_search(
+    cfg: &Config,
+    es_client: Option<&ElasticsearchClient>,
+    query: &str,
+    guild_id: Option<&str>,
+    channel_id: Option<&str>,
+    author_id: Option<&str>,
+    top_k: usize,
+    opts: &HybridSearchConfig,
+) -> Result<Vec<FusedResult>, DynErr> {
+    let _timer = HYBRID_SEARCH_DURATION.start_timer();
+
+    let mut pinecone_filters = QueryFilters::builder();
+    if let Some(channel_id) = channel_id {
+        pinecone_filters = pinecone_filters.channel_ids(vec![channel_id.to_string()]);
+    }
+    if let Some(author_id) = author_id {
+        pinecone_filters = pinecone_filters.author_id(author_id);
+    }
+    let pinecone_filters = pinecone_filters.build();
+
+    let pinecone_fut = async {
+        let embedding = get_embedding(cfg, query, EmbeddingInputType::SearchQuery).await?;
+        query_chunks_pinecone(cfg, embedding, opts.pinecone_depth, guild_id.map(|g| g.to_string()), None, &pinecone_filters).await
+    };
+
+    let es_fut = async {
+        match es_client {
+            Some(client) => client.search_messages(query, guild_id, channel_id, author_id, opts.es_depth).await,
+            None => Ok(vec![]),
+        }
+    };
+
+    let (pinecone_results, es_results) = tokio::join!(pinecone_fut, es_fut);
+    let pinecone_results = pinecone_results?;
+    let es_results = es_results?;
+
+    // Shared across both retrievers so the same item surfaced by both
+    // accumulates both RRF terms: Pinecone's `chunk_id` and Elasticsearch's
+    // result carry no common identifier, but their `text` does.
+    fn normalized_text_key(text: &str) -> String {
+        text.trim().to_lowercase()
+    }
+
+    let mut fused: HashMap<String, (f64, FusedResult)> = HashMap::new();
+
+    for (rank, chunk) in pinecone_results.into_iter().enumerate() {
+        let contribution = opts.pinecone_weight / (opts.k + (rank + 1) as f64);
+        let entry = fused.entry(normalized_text_key(&chunk.text)).or_insert_with(|| {
+            (
+                0.0,
+                FusedResult {
+                    text: chunk.text.clone(),
+                    author_id: chunk.authors.first().cloned().unwrap_or_else(|| "unknown".to_string()),
+                    timestamp: chunk.first_timestamp.clone(),
+                    score: 0.0,
+                },
+            )
+        });
+        entry.0 += contribution;
+    }
+
+    for (rank, msg) in es_results.into_iter().enumerate() {
+        let contribution = opts.es_weight / (opts.k + (rank + 1) as f64);
+        let entry = fused.entry(normalized_text_key(&msg.text)).or_insert_with(|| {
+            (
+                0.0,
+                FusedResult {
+                    text: msg.text.clone(),
+                    author_id: msg.author_id.clone(),
+                    timestamp: msg.timestamp.clone(),
+                    score: 0.0,
+                },
+            )
+        });
+        entry.0 += contribution;
+    }
+
+    let mut results: Vec<FusedResult> = fused
+        .into_values()
+        .map(|(score, mut result)| {
+            result.score = score;
+            result
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(top_k);
+
+    info!(count = results.len(), "Fused hybrid search results");
+    Ok(results)
+}
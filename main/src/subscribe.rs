@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::info;
+use crate::{
+    config::Config,
+    cohere::{get_embedding, EmbeddingInputType},
+    pinecone::query_pinecone,
+    elasticsearch::{ElasticsearchClient, ESQueryResult},
+    schema::{QueryResult, QueryFilters},
+};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Assigns the monotonically increasing `seq` every Pinecone/ElasticSearch
+/// upsert is tagged with, and wakes anyone blocked in [`poll_changes`] when
+/// it advances. Shared across both backends so a single cursor space covers
+/// whichever backend indexed a given message.
+pub struct IngestionSequencer {
+    counter: AtomicU64,
+    tx: watch::Sender<u64>,
+}
+
+impl IngestionSequencer {
+    fn new() -> Self {
+        let (tx, _rx) = watch::channel(0);
+        Self { counter: AtomicU64::new(0), tx }
+    }
+
+    /// Returns the next sequence number and notifies waiters.
+    pub fn next_seq(&self) -> u64 {
+        let seq = self.counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = self.tx.send(seq);
+        seq
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for IngestionSequencer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref INGESTION_SEQ: IngestionSequencer = IngestionSequencer::new();
+}
+
+/// Tuning for [`poll_changes`].
+#[derive(Debug, Clone, Copy)]
+pub struct PollConfig {
+    pub timeout: Duration,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(30) }
+    }
+}
+
+/// Result of a [`poll_changes`] call: the new matches (if any) and the
+/// cursor the caller should pass on the next call.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionUpdate {
+    pub pinecone_matches: Vec<QueryResult>,
+    pub es_matches: Vec<ESQueryResult>,
+    pub cursor: u64,
+}
+
+/// Long-polls for messages ingested after `cursor` that match `query`,
+/// modeled on a K2V-style causal poll: blocks until either a newly indexed
+/// `MessageEvent` matches, or `opts.timeout` elapses, then returns the new
+/// matches and an advanced cursor. On timeout the cursor comes back
+/// unchanged so the caller can immediately re-poll.
+pub async fn poll_changes(
+    cfg: &Config,
+    es_client: Option<&ElasticsearchClient>,
+    query: &str,
+    guild_id: Option<&str>,
+    channel_id: Option<&str>,
+    author_id: Option<&str>,
+    mut cursor: u64,
+    top_k: usize,
+    opts: &PollConfig,
+) -> Result<SubscriptionUpdate, DynErr> {
+    let mut rx = INGESTION_SEQ.subscribe();
+    let deadline = Instant::now() + opts.timeout;
+    let start_cursor = cursor;
+
+    loop {
+        let latest = *rx.borrow();
+        if latest > cursor {
+            let embedding = get_embedding(cfg, query, EmbeddingInputType::SearchQuery).await?;
+            let mut filters = QueryFilters::builder();
+            if let Some(channel_id) = channel_id {
+                filters = filters.channel_ids(vec![channel_id.to_string()]);
+            }
+            if let Some(author_id) = author_id {
+                filters = filters.author_id(author_id);
+            }
+            let pinecone_matches: Vec<QueryResult> = query_pinecone(cfg, embedding, top_k, guild_id.map(|g| g.to_string()), None, &filters.build())
+                .await?
+                .into_iter()
+                .filter(|r| r.seq > cursor)
+                .collect();
+
+            let es_matches: Vec<ESQueryResult> = match es_client {
+                Some(client) => client
+                    .search_messages(query, guild_id, channel_id, author_id, top_k)
+                    .await?
+                    .into_iter()
+                    .filter(|r| r.seq > cursor)
+                    .collect(),
+                None => vec![],
+            };
+
+            if !pinecone_matches.is_empty() || !es_matches.is_empty() {
+                info!(
+                    cursor,
+                    latest,
+                    pinecone = pinecone_matches.len(),
+                    es = es_matches.len(),
+                    "Subscription poll found new matches"
+                );
+                return Ok(SubscriptionUpdate { pinecone_matches, es_matches, cursor: latest });
+            }
+
+            // Nothing matched this round of ingestion; advance past it so we
+            // don't keep re-querying for seqs we've already ruled out.
+            cursor = latest;
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(SubscriptionUpdate { cursor: start_cursor, ..Default::default() });
+        }
+
+        if tokio::time::timeout(remaining, rx.changed()).await.is_err() {
+            return Ok(SubscriptionUpdate { cursor: start_cursor, ..Default::default() });
+        }
+    }
+}
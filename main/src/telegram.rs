@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tracing::{info, warn};
+use crate::{
+    config::Config,
+    http_client::shared_client,
+    message_source::MessageSource,
+    schema::{MessageEvent, Platform},
+};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Deserialize)]
+struct GetUpdatesResponse {
+    ok: bool,
+    result: Vec<TelegramUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramUpdate {
+    update_id: i64,
+    message: Option<TelegramMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramMessage {
+    message_id: i64,
+    date: i64,
+    chat: TelegramChat,
+    from: Option<TelegramUser>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramChat {
+    id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramUser {
+    id: i64,
+    is_bot: bool,
+}
+
+/// [`MessageSource`] that long-polls Telegram's `getUpdates` and maps each
+/// update into a [`MessageEvent`], the same shape Discord's gateway
+/// `message` handler produces, so the rest of the pipeline needs no
+/// Telegram-specific code. Telegram has no guild concept, so `guild_id` is
+/// always `None` and `channel_id` is the chat id - mirroring how this crate
+/// already treats a guild-less `channel_id` as a DM.
+pub struct TelegramSource {
+    bot_token: String,
+    poll_timeout_secs: u64,
+    offset: AtomicI64,
+}
+
+impl TelegramSource {
+    pub fn new(cfg: &Config) -> Option<Self> {
+        let bot_token = cfg.telegram_bot_token.clone()?;
+        Some(Self {
+            bot_token,
+            poll_timeout_secs: cfg.telegram_poll_timeout_secs,
+            offset: AtomicI64::new(0),
+        })
+    }
+
+    fn api_url(&self, method: &str) -> String {
+        format!("https://api.telegram.org/bot{}/{}", self.bot_token, method)
+    }
+}
+
+#[async_trait]
+impl MessageSource for TelegramSource {
+    fn platform(&self) -> Platform {
+        Platform::Telegram
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn poll(&self) -> Result<Vec<MessageEvent>, DynErr> {
+        let offset = self.offset.load(Ordering::SeqCst);
+
+        let res = shared_client()
+            .get(self.api_url("getUpdates"))
+            .query(&[
+                ("offset", offset.to_string()),
+                ("timeout", self.poll_timeout_secs.to_string()),
+            ])
+            .timeout(std::time::Duration::from_secs(self.poll_timeout_secs + 10))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(format!("Telegram getUpdates error: {}", res.text().await?).into());
+        }
+
+        let body: GetUpdatesResponse = res.json().await?;
+        if !body.ok {
+            return Err("Telegram getUpdates returned ok=false".into());
+        }
+
+        let mut events = Vec::new();
+        let mut highest_update_id = offset - 1;
+
+        for update in body.result {
+            highest_update_id = highest_update_id.max(update.update_id);
+
+            let Some(message) = update.message else { continue };
+            let Some(text) = message.text else { continue };
+            let is_bot = message.from.as_ref().is_some_and(|from| from.is_bot);
+            if is_bot {
+                continue;
+            }
+            let Some(from) = message.from else {
+                warn!(update_id = update.update_id, "Telegram message has no sender, skipping");
+                continue;
+            };
+
+            let timestamp = chrono::DateTime::from_timestamp(message.date, 0)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default();
+
+            events.push(MessageEvent {
+                id: format!("tg:{}:{}", message.chat.id, message.message_id),
+                platform: Platform::Telegram,
+                guild_id: None,
+                channel_id: message.chat.id.to_string(),
+                author_id: from.id.to_string(),
+                timestamp,
+                text,
+            });
+        }
+
+        // Acknowledges every update we saw (even ones we skipped, like bot
+        // messages) so Telegram never redelivers them, matching
+        // `getUpdates`' "offset = highest seen update_id + 1" contract.
+        self.offset.store(highest_update_id + 1, Ordering::SeqCst);
+
+        info!(count = events.len(), "Polled Telegram updates");
+        Ok(events)
+    }
+}
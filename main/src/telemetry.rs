@@ -0,0 +1,120 @@
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::Sampler;
+use opentelemetry_sdk::Resource;
+use rdkafka::message::{BorrowedHeaders, Header, Headers, OwnedHeaders};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use crate::config::Config;
+
+/// Initializes the global `tracing` subscriber: the usual `fmt` layer plus,
+/// when `cfg.otlp_endpoint` is set, a `tracing-opentelemetry` layer that
+/// ships spans to that OTLP collector. If the exporter can't be built (bad
+/// endpoint, collector unreachable at startup), tracing falls back to
+/// `fmt`-only output rather than failing the whole process, matching how
+/// `KafkaConsumer::new` degrades when the DLQ producer can't be built.
+pub fn init_tracing(cfg: &Config) {
+    // Without a global propagator, `global::get_text_map_propagator` falls
+    // back to a no-op one: `inject_trace_context`/`extract_trace_context`
+    // would silently stop writing/reading the `traceparent` header, breaking
+    // the Discord -> Kafka -> consumer -> Pinecone trace linkage entirely.
+    opentelemetry::global::set_text_map_propagator(
+        opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+    );
+
+    let fmt_layer = tracing_subscriber::fmt::layer();
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry().with(env_filter).with(fmt_layer);
+
+    match build_tracer(cfg) {
+        Some(tracer) => registry.with(tracing_opentelemetry::layer().with_tracer(tracer)).init(),
+        None => registry.init(),
+    }
+}
+
+fn build_tracer(cfg: &Config) -> Option<opentelemetry_sdk::trace::Tracer> {
+    let endpoint = cfg.otlp_endpoint.as_ref()?;
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            opentelemetry_sdk::trace::config()
+                .with_sampler(Sampler::AlwaysOn)
+                .with_resource(Resource::new(vec![opentelemetry::KeyValue::new(
+                    "service.name",
+                    cfg.otel_service_name.clone(),
+                )])),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio);
+
+    match tracer {
+        Ok(tracer) => Some(tracer),
+        Err(err) => {
+            tracing::warn!(error = %err, endpoint, "Failed to initialize OTLP exporter, continuing with fmt-only tracing");
+            None
+        }
+    }
+}
+
+/// Adapts `OwnedHeaders` to the `Injector` trait the W3C `traceparent`
+/// propagator expects, since rdkafka only exposes a rebuild-on-insert API
+/// rather than a `HashMap`-like one.
+struct KafkaHeaderInjector<'a>(&'a mut OwnedHeaders);
+
+impl Injector for KafkaHeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let headers = std::mem::take(self.0);
+        *self.0 = headers.insert(Header { key, value: Some(&value) });
+    }
+}
+
+/// Adapts `BorrowedHeaders` (what `Message::headers()` returns on consume)
+/// to the `Extractor` trait so the propagator can read a remote context
+/// back out.
+struct KafkaHeaderExtractor<'a>(&'a BorrowedHeaders);
+
+impl Extractor for KafkaHeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        (0..self.0.count())
+            .filter_map(|i| self.0.get_as::<str>(i).ok())
+            .find(|header| header.key == key)
+            .and_then(|header| header.value)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        (0..self.0.count())
+            .filter_map(|i| self.0.get_as::<str>(i).ok())
+            .map(|header| header.key)
+            .collect()
+    }
+}
+
+/// Injects `span`'s trace context into fresh Kafka headers via the global
+/// W3C propagator, so whoever consumes this message can continue the same
+/// trace instead of starting a disconnected one.
+pub fn inject_trace_context(span: &Span) -> OwnedHeaders {
+    let cx = span.context();
+    let mut headers = OwnedHeaders::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut KafkaHeaderInjector(&mut headers));
+    });
+    headers
+}
+
+/// Extracts a remote trace context from a consumed message's Kafka headers,
+/// if any were set, so `KafkaConsumer::start_consuming` can attach it as the
+/// root span's parent.
+pub fn extract_trace_context(headers: Option<&BorrowedHeaders>) -> opentelemetry::Context {
+    match headers {
+        Some(headers) => opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&KafkaHeaderExtractor(headers))
+        }),
+        None => opentelemetry::Context::new(),
+    }
+}
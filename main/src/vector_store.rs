@@ -0,0 +1,194 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use tracing::info;
+use crate::{
+    config::Config,
+    schema::{MessageEvent, MessageChunk, QueryResult, ChunkQueryResult, QueryFilters},
+    hnsw::{HnswIndex, VectorMetadata, ChunkFields},
+    pinecone::{upsert_to_pinecone, upsert_chunk_to_pinecone, query_pinecone, query_chunks_pinecone},
+};
+
+type DynErr = Box<dyn std::error::Error + Send + Sync>;
+
+/// Selects which [`VectorStore`] implementation the application should
+/// construct at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorStoreBackend {
+    Pinecone,
+    Hnsw,
+}
+
+impl VectorStoreBackend {
+    pub fn from_env_str(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "hnsw" | "local" => VectorStoreBackend::Hnsw,
+            _ => VectorStoreBackend::Pinecone,
+        }
+    }
+}
+
+/// Abstracts over the vector-store operations the rest of the crate relies
+/// on, so a managed backend (Pinecone) and an embedded backend (HNSW) can
+/// be swapped in behind the same `guild_id`/namespace filtering semantics.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    async fn upsert(&self, msg: &MessageEvent, embedding: Vec<f32>) -> Result<(), DynErr>;
+    async fn upsert_chunk(&self, chunk: &MessageChunk, embedding: Vec<f32>) -> Result<(), DynErr>;
+    async fn query(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<QueryResult>, DynErr>;
+    async fn query_chunks(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<ChunkQueryResult>, DynErr>;
+}
+
+/// Builds the configured [`VectorStore`] implementation at startup.
+pub fn build_vector_store(cfg: &Config) -> Box<dyn VectorStore> {
+    match VectorStoreBackend::from_env_str(&cfg.vector_store_backend) {
+        VectorStoreBackend::Pinecone => Box::new(PineconeVectorStore::new(cfg.clone())),
+        VectorStoreBackend::Hnsw => Box::new(HnswVectorStore::new(PathBuf::from(&cfg.hnsw_persist_path))),
+    }
+}
+
+pub struct PineconeVectorStore {
+    cfg: Config,
+}
+
+impl PineconeVectorStore {
+    pub fn new(cfg: Config) -> Self {
+        Self { cfg }
+    }
+}
+
+#[async_trait]
+impl VectorStore for PineconeVectorStore {
+    async fn upsert(&self, msg: &MessageEvent, embedding: Vec<f32>) -> Result<(), DynErr> {
+        upsert_to_pinecone(&self.cfg, msg, embedding).await
+    }
+
+    async fn upsert_chunk(&self, chunk: &MessageChunk, embedding: Vec<f32>) -> Result<(), DynErr> {
+        upsert_chunk_to_pinecone(&self.cfg, chunk, embedding).await
+    }
+
+    async fn query(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<QueryResult>, DynErr> {
+        query_pinecone(&self.cfg, embedding, top_k, guild_id, None, &QueryFilters::default()).await
+    }
+
+    async fn query_chunks(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<ChunkQueryResult>, DynErr> {
+        query_chunks_pinecone(&self.cfg, embedding, top_k, guild_id, None, &QueryFilters::default()).await
+    }
+}
+
+/// In-process vector store backed by an [`HnswIndex`] persisted to disk,
+/// for self-hosted deployments that don't want a Pinecone dependency.
+pub struct HnswVectorStore {
+    index: Mutex<HnswIndex>,
+    persist_path: PathBuf,
+}
+
+impl HnswVectorStore {
+    pub fn new(persist_path: PathBuf) -> Self {
+        let index = HnswIndex::load(&persist_path).unwrap_or_else(|_| HnswIndex::new(16, 200, 64));
+        Self {
+            index: Mutex::new(index),
+            persist_path,
+        }
+    }
+
+    fn persist(&self) {
+        let index = self.index.lock().unwrap();
+        if let Err(err) = index.save(&self.persist_path) {
+            tracing::error!(error = %err, "Failed to persist HNSW index");
+        }
+    }
+
+    fn matches_guild(metadata: &VectorMetadata, guild_id: &Option<String>) -> bool {
+        match guild_id {
+            Some(gid) => metadata.guild_id.as_deref() == Some(gid.as_str()),
+            None => metadata.guild_id.is_none(),
+        }
+    }
+}
+
+#[async_trait]
+impl VectorStore for HnswVectorStore {
+    async fn upsert(&self, msg: &MessageEvent, embedding: Vec<f32>) -> Result<(), DynErr> {
+        let metadata = VectorMetadata {
+            guild_id: msg.guild_id.clone(),
+            author_id: Some(msg.author_id.clone()),
+            timestamp: msg.timestamp.clone(),
+            text: msg.text.clone(),
+            is_chunk: false,
+            chunk_fields: None,
+        };
+        {
+            let mut index = self.index.lock().unwrap();
+            index.insert(msg.id.clone(), embedding, metadata);
+        }
+        self.persist();
+        info!(msg_id = %msg.id, "Upserted to embedded HNSW index");
+        Ok(())
+    }
+
+    async fn upsert_chunk(&self, chunk: &MessageChunk, embedding: Vec<f32>) -> Result<(), DynErr> {
+        let metadata = VectorMetadata {
+            guild_id: chunk.guild_id.clone(),
+            author_id: None,
+            timestamp: chunk.first_timestamp.clone(),
+            text: chunk.full_text.clone(),
+            is_chunk: true,
+            chunk_fields: Some(ChunkFields {
+                chunk_id: chunk.chunk_id.clone(),
+                summary: chunk.summary.clone(),
+                authors: chunk.authors.clone(),
+                message_count: chunk.message_count,
+                first_timestamp: chunk.first_timestamp.clone(),
+                last_timestamp: chunk.last_timestamp.clone(),
+            }),
+        };
+        {
+            let mut index = self.index.lock().unwrap();
+            index.insert(format!("chunk_{}", chunk.chunk_id), embedding, metadata);
+        }
+        self.persist();
+        info!(chunk_id = %chunk.chunk_id, "Upserted chunk to embedded HNSW index");
+        Ok(())
+    }
+
+    async fn query(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<QueryResult>, DynErr> {
+        let index = self.index.lock().unwrap();
+        let matches = index.search(&embedding, top_k, |m| !m.is_chunk && Self::matches_guild(m, &guild_id));
+
+        Ok(matches
+            .into_iter()
+            .map(|(_, distance, metadata)| QueryResult {
+                text: metadata.text,
+                author_id: metadata.author_id.unwrap_or_else(|| "unknown".to_string()),
+                timestamp: metadata.timestamp,
+                score: (1.0 - distance) as f64,
+                // The embedded HNSW backend doesn't track an ingestion
+                // sequence, so it isn't a source for `subscribe::poll_changes`.
+                seq: 0,
+            })
+            .collect())
+    }
+
+    async fn query_chunks(&self, embedding: Vec<f32>, top_k: usize, guild_id: Option<String>) -> Result<Vec<ChunkQueryResult>, DynErr> {
+        let index = self.index.lock().unwrap();
+        let matches = index.search(&embedding, top_k, |m| m.is_chunk && Self::matches_guild(m, &guild_id));
+
+        Ok(matches
+            .into_iter()
+            .filter_map(|(_, distance, metadata)| {
+                let fields = metadata.chunk_fields?;
+                Some(ChunkQueryResult {
+                    chunk_id: fields.chunk_id,
+                    text: metadata.text,
+                    summary: fields.summary,
+                    authors: fields.authors,
+                    message_count: fields.message_count,
+                    first_timestamp: fields.first_timestamp,
+                    last_timestamp: fields.last_timestamp,
+                    score: (1.0 - distance) as f64,
+                })
+            })
+            .collect())
+    }
+}